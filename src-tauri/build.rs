@@ -1,6 +1,9 @@
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
 
 fn main() {
     let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -12,23 +15,92 @@ fn main() {
     let manifest_resources_dir = manifest_dir.join("resources");
     let manifest_resource_path = manifest_resources_dir.join("veil.gguf");
 
-    if !manifest_resource_path.exists() {
-        if resource_path.exists() {
+    let source_path = if resource_path.exists() {
+        Some(resource_path.clone())
+    } else {
+        env::var("VEIL_MODEL_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .filter(|path| path.exists())
+    };
+
+    if let Some(source_path) = &source_path {
+        // The manifest is hashed from the source, never from the copy, so a
+        // copy cut short by an interrupted build fails verification at load.
+        ensure_checksum_manifest(source_path);
+        // A partial copy would otherwise stick around forever.
+        if file_len(&manifest_resource_path) != file_len(source_path) {
             let _ = fs::create_dir_all(&manifest_resources_dir);
-            let _ = fs::copy(&resource_path, &manifest_resource_path);
-        } else if let Ok(override_path) = env::var("VEIL_MODEL_PATH") {
-            let override_path = PathBuf::from(override_path);
-            if override_path.exists() {
-                let _ = fs::create_dir_all(&manifest_resources_dir);
-                let _ = fs::copy(&override_path, &manifest_resource_path);
-            }
+            let _ = fs::copy(source_path, &manifest_resource_path);
         }
+        let _ = fs::copy(
+            checksum_manifest_path(source_path),
+            checksum_manifest_path(&manifest_resource_path),
+        );
     }
+    // Without a source the bundled copy gets no manifest: one hashed from the
+    // copy itself would vouch for whatever a cut-short build left behind.
 
     if !resource_path.exists() && manifest_resource_path.exists() {
         let _ = fs::create_dir_all(&resources_dir);
         let _ = fs::copy(&manifest_resource_path, &resource_path);
+        let _ = fs::copy(
+            checksum_manifest_path(&manifest_resource_path),
+            checksum_manifest_path(&resource_path),
+        );
     }
 
     tauri_build::build()
 }
+
+fn file_len(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
+/// Same naming as `integrity::manifest_path`: `veil.gguf.manifest.json`.
+fn checksum_manifest_path(model_path: &Path) -> PathBuf {
+    let mut file_name = model_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".manifest.json");
+    model_path.with_file_name(file_name)
+}
+
+/// Writes the manifest when it is missing or older than the model. Hashing a
+/// multi-GB model is slow, so an up-to-date manifest is left alone.
+fn ensure_checksum_manifest(model_path: &Path) {
+    let manifest_path = checksum_manifest_path(model_path);
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    if let (Some(manifest_time), Some(model_time)) =
+        (modified(&manifest_path), modified(model_path))
+    {
+        if manifest_time >= model_time {
+            return;
+        }
+    }
+
+    let Ok(mut file) = fs::File::open(model_path) else {
+        return;
+    };
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 8 * 1024 * 1024];
+    let mut size_bytes = 0u64;
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => {
+                hasher.update(&buffer[..read]);
+                size_bytes += read as u64;
+            }
+            Err(_) => return,
+        }
+    }
+    let sha256: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    // Same shape as `integrity::ModelManifest`.
+    let manifest = serde_json::json!({ "sha256": sha256, "sizeBytes": size_bytes });
+    if let Ok(json) = serde_json::to_string_pretty(&manifest) {
+        let _ = fs::write(&manifest_path, json);
+    }
+}
@@ -0,0 +1,188 @@
+// A stand-in for an OpenAI-compatible server, for trying the `openai` backend
+// without a model on hand. Every chat completion answers with the same
+// reading, streamed word by word when the request asks for a stream;
+// dashboard requests get it too and exercise the re-prompt and fallback path.
+// The `openai` backend's tests run it in-process on an ephemeral port.
+//
+//     cargo run --example openai_stand_in -- 8089
+//     VEIL_OPENAI_BASE_URL=http://127.0.0.1:8089/v1 npm run tauri dev -- --features openai
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+const DEFAULT_PORT: u16 = 8089;
+pub const CONTEXT_LENGTH: u32 = 4096;
+const STREAM_DELAY: Duration = Duration::from_millis(30);
+
+fn main() {
+    let port = std::env::args()
+        .nth(1)
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+    let listener = TcpListener::bind(("127.0.0.1", port)).expect("port should be free");
+    eprintln!("[Veil] stand-in listening on http://127.0.0.1:{}/v1", port);
+    serve(listener);
+}
+
+/// Answers every connection on `listener`, each on its own thread.
+pub fn serve(listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        std::thread::spawn(move || {
+            if let Err(error) = handle(stream) {
+                eprintln!("[Veil] stand-in connection error={}", error);
+            }
+        });
+    }
+}
+
+fn handle(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0usize;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    eprintln!("[Veil] stand-in {} {}", method, path);
+    match (method, path) {
+        ("GET", "/v1/models") => respond_json(
+            &mut stream,
+            "200 OK",
+            &json!({ "object": "list", "data": [{ "id": "veil", "object": "model" }] }),
+        ),
+        // llama.cpp's native endpoints, used for context budgeting.
+        ("GET", "/props") => respond_json(
+            &mut stream,
+            "200 OK",
+            &json!({ "default_generation_settings": { "n_ctx": CONTEXT_LENGTH } }),
+        ),
+        ("POST", "/tokenize") => {
+            let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+            let words = request["content"].as_str().unwrap_or("").split_whitespace().count();
+            let tokens: Vec<usize> = (0..words).collect();
+            respond_json(&mut stream, "200 OK", &json!({ "tokens": tokens }))
+        }
+        ("POST", "/v1/chat/completions") => {
+            let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+            let content = canned_reading().to_string();
+            // Whitespace-separated words stand in for tokens.
+            let usage = json!({
+                "prompt_tokens": request["messages"].to_string().split_whitespace().count(),
+                "completion_tokens": content.split_whitespace().count(),
+            });
+            if request["stream"].as_bool() == Some(true) {
+                let include_usage = request["stream_options"]["include_usage"].as_bool() == Some(true);
+                respond_stream(&mut stream, &content, include_usage.then_some(usage))
+            } else {
+                respond_json(
+                    &mut stream,
+                    "200 OK",
+                    &json!({
+                        "object": "chat.completion",
+                        "choices": [{
+                            "index": 0,
+                            "message": { "role": "assistant", "content": content },
+                            "finish_reason": "stop",
+                        }],
+                        "usage": usage,
+                    }),
+                )
+            }
+        }
+        _ => respond_json(
+            &mut stream,
+            "404 Not Found",
+            &json!({ "error": { "message": format!("No route for {} {}", method, path) } }),
+        ),
+    }
+}
+
+fn respond_json(stream: &mut TcpStream, status: &str, body: &Value) -> std::io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Server-sent events, one delta per word. The body runs until the connection
+/// closes, so no length or chunked encoding is needed. Each event goes out in
+/// two writes, split inside a multi-byte character when it has one, so a
+/// client has to buffer partial lines and bytes.
+fn respond_stream(
+    stream: &mut TcpStream,
+    content: &str,
+    usage: Option<Value>,
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n"
+    )?;
+    for word in content.split_inclusive(' ') {
+        let event = json!({
+            "object": "chat.completion.chunk",
+            "choices": [{ "index": 0, "delta": { "content": word }, "finish_reason": null }],
+        });
+        write_split(stream, &format!("data: {}\n\n", event))?;
+        std::thread::sleep(STREAM_DELAY);
+    }
+    let finish = json!({
+        "object": "chat.completion.chunk",
+        "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }],
+    });
+    write!(stream, "data: {}\n\n", finish)?;
+    if let Some(usage) = usage {
+        let event = json!({ "object": "chat.completion.chunk", "choices": [], "usage": usage });
+        write!(stream, "data: {}\n\n", event)?;
+    }
+    write!(stream, "data: [DONE]\n\n")
+}
+
+fn write_split(stream: &mut TcpStream, event: &str) -> std::io::Result<()> {
+    let split = event
+        .bytes()
+        .position(|byte| !byte.is_ascii())
+        .map(|start| start + 1)
+        .unwrap_or(event.len() / 2);
+    stream.write_all(&event.as_bytes()[..split])?;
+    stream.flush()?;
+    // Gives the client a chance to read the first half on its own.
+    std::thread::sleep(Duration::from_millis(2));
+    stream.write_all(&event.as_bytes()[split..])?;
+    stream.flush()
+}
+
+fn canned_reading() -> Value {
+    let now = chrono::Local::now();
+    json!({
+        "date": now.format("%Y-%m-%d").to_string(),
+        "sign": "Aries",
+        "title": "A Quiet Signal",
+        "message": "The answer you are waiting on arrives from a local server today — let it speak plainly and take it at its word.",
+        "themes": ["patience", "clarity", "small wins"],
+        "affirmation": "I trust what is close at hand.",
+        "luckyColor": "slate",
+        "luckyNumber": 7,
+        "createdAt": now.to_rfc3339(),
+        "source": "model",
+    })
+}
@@ -0,0 +1,88 @@
+use crate::backends::{build_fallback_prompt, system_prompt, BackendError, HoroscopeModelBackend};
+use crate::types::{ReadingRequest, TokenEstimate};
+
+// Keeps prompt plus `max_tokens` inside the model's context window. A request
+// that overruns it does not fail outright: the engine stops decoding when the
+// window fills, which leaves truncated JSON that only looks like a model
+// mistake. Counting first turns that into a smaller `max_tokens` or, when too
+// little room is left for any useful output, a precise error.
+
+/// Used when the backend cannot tokenize: English prose runs about four bytes
+/// a token, so three errs towards clamping early.
+const BYTES_PER_TOKEN_ESTIMATE: usize = 3;
+/// Chat-template markup, for estimated counts.
+const ESTIMATE_OVERHEAD_TOKENS: u32 = 32;
+
+/// Counts `prompt` the way the backend would feed it to the model, after
+/// `system_prompt` and with the chat template, estimating from its length
+/// when the backend has no tokenizer to ask.
+pub(crate) async fn measure_prompt(
+    backend: &dyn HoroscopeModelBackend,
+    system_prompt: &str,
+    prompt: &str,
+) -> TokenEstimate {
+    let counted = backend.count_prompt_tokens(system_prompt, prompt).await;
+    let prompt_tokens = counted.unwrap_or_else(|| estimate_prompt_tokens(system_prompt, prompt));
+    let context_length = backend.context_length();
+    TokenEstimate {
+        prompt_tokens,
+        exact: counted.is_some(),
+        context_length,
+        available_tokens: context_length.map(|context| context.saturating_sub(prompt_tokens)),
+    }
+}
+
+fn estimate_prompt_tokens(system_prompt: &str, prompt: &str) -> u32 {
+    let tokens = (system_prompt.len() + prompt.len()).div_ceil(BYTES_PER_TOKEN_ESTIMATE);
+    u32::try_from(tokens)
+        .unwrap_or(u32::MAX)
+        .saturating_add(ESTIMATE_OVERHEAD_TOKENS)
+}
+
+/// Lowers `request.sampling.max_tokens` to what the context window has left
+/// after the prompt. Fails when that is less than `min_output` tokens (or
+/// the requested `max_tokens`, if smaller), since output cut off that early
+/// cannot parse. Backends that do not know their context length are left
+/// alone.
+pub(crate) async fn fit_max_tokens(
+    backend: &dyn HoroscopeModelBackend,
+    request: &mut ReadingRequest,
+    min_output: u32,
+) -> Result<(), BackendError> {
+    if backend.context_length().is_none() {
+        return Ok(());
+    }
+    let prompt = request
+        .prompt
+        .clone()
+        .unwrap_or_else(|| build_fallback_prompt(request));
+    let system_prompt = system_prompt(&request.persona);
+    let estimate = measure_prompt(backend, &system_prompt, &prompt).await;
+    let (Some(context_length), Some(available)) =
+        (estimate.context_length, estimate.available_tokens)
+    else {
+        return Ok(());
+    };
+    let requested = request.sampling.max_tokens;
+    if requested <= available {
+        return Ok(());
+    }
+    let needed = min_output.min(requested);
+    if available < needed {
+        return Err(BackendError::ContextOverflow(format!(
+            "The prompt is {} tokens{} and the model's context window is {}, leaving {} for \
+             output where at least {} are needed.",
+            estimate.prompt_tokens,
+            if estimate.exact { "" } else { " (estimated)" },
+            context_length,
+            available,
+            needed
+        )));
+    }
+    eprintln!(
+        "[Veil] model:budget:clamped maxTokens={}->{} promptTokens={} exact={} contextLength={}",
+        requested, available, estimate.prompt_tokens, estimate.exact, context_length
+    );
+    request.sampling.max_tokens = available;
+    Ok(())
+}
@@ -1,120 +1,392 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
-use crate::backends::EmbeddedBackend;
+use crate::backends::{
+    build_fallback_prompt, system_prompt, BackendError, Completion, CompletionUsage,
+    HoroscopeModelBackend, StubBackend,
+};
+use crate::budget;
+use crate::dashboard::{format_field_errors, parse_dashboard_payload, DashboardPayload};
+use crate::generation::{CancelReason, CancelToken, GenerationGuard, Watchdog};
+use crate::gguf::inspect_gguf;
+use crate::memory;
+use crate::message_stream::MessageStream;
+use crate::model_import;
+#[cfg(feature = "mistral")]
+use crate::model_loader;
 use crate::model_manager::{ModelManager, ReadingSource};
+use crate::model_registry;
+#[cfg(feature = "openai")]
+use crate::openai;
+use crate::personas;
+use crate::prompts;
+use crate::queue::QueuePermit;
+use crate::repair::{build_reprompt, repair_json};
+use crate::sampling_presets;
+use crate::sanitize::sanitize_request;
 use crate::stub::{generate_stub_dashboard, generate_stub_reading};
-use crate::types::{ModelStatus, Profile, Reading, ReadingRequest, SamplingParams, StreamEvent};
+use crate::types::{
+    FallbackReason, GenerationError, GenerationOutcome, GenerationPriority, GgufInfo,
+    InferenceMetrics, ModelEntry, ModelMetrics, ModelStatus, Persona, Profile, PromptStamp,
+    PromptTemplateInfo, Reading, ReadingRequest, SamplingParams, SamplingPreset, SanitizedField,
+    StreamEvent, SystemResources, TokenEstimate,
+};
 
 #[tauri::command]
 pub async fn init_model(state: State<'_, ModelManager>, app: AppHandle) -> Result<ModelStatus, String> {
     let status = state.get_status();
     if matches!(
         status,
-        ModelStatus::Loading { .. } | ModelStatus::Loaded { .. }
+        ModelStatus::Loading { .. }
+            | ModelStatus::Loaded { .. }
+            | ModelStatus::Unloading
+            | ModelStatus::EngineUnavailable { .. }
     ) {
         return Ok(status);
     }
 
-    state.set_status(ModelStatus::Loading { progress: 0.1 });
-    emit_status(&app, state.get_status());
-
-    let state_clone = state.inner().clone();
-    let app_clone = app.clone();
-    tauri::async_runtime::spawn(run_model_load(state_clone, app_clone));
+    Ok(start_model_load(state.inner(), &app))
+}
 
+/// Drops the loaded model after in-flight generations finish, returning the
+/// app to the stub backend.
+#[tauri::command]
+pub async fn unload_model(state: State<'_, ModelManager>, app: AppHandle) -> Result<ModelStatus, String> {
+    unload(state.inner(), &app).await?;
     Ok(state.get_status())
 }
 
-async fn run_model_load(state: ModelManager, app: AppHandle) {
-    let mut progress = 0.1_f32;
-    let mut interval = tokio::time::interval(Duration::from_millis(180));
-    let app_for_load = app.clone();
-    let mut load_handle = tauri::async_runtime::spawn_blocking(move || {
-        tauri::async_runtime::block_on(async {
-            let model_path = resolve_model_path(&app_for_load)?;
-            EmbeddedBackend::load(model_path).await
-        })
-    });
+/// Unloads the current model and loads the same file again.
+#[tauri::command]
+pub async fn reload_model(state: State<'_, ModelManager>, app: AppHandle) -> Result<ModelStatus, String> {
+    unload(state.inner(), &app).await?;
+    Ok(start_model_load(state.inner(), &app))
+}
 
-    let load_result = loop {
-        tokio::select! {
-            _ = interval.tick() => {
-                if progress < 0.9 {
-                    let next_progress = (progress + 0.15).min(0.9);
-                    if (next_progress - progress).abs() > f32::EPSILON {
-                        progress = next_progress;
-                        state.set_status(ModelStatus::Loading { progress });
-                        emit_status(&app, state.get_status());
-                    }
-                }
-            }
-            result = &mut load_handle => {
-                break result;
+/// Unloads the current model and loads the GGUF at `path` instead.
+#[tauri::command]
+pub async fn switch_model(
+    state: State<'_, ModelManager>,
+    app: AppHandle,
+    path: String,
+) -> Result<ModelStatus, String> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(format!("Model path {} is not a file.", path.display()));
+    }
+    if !model_registry::is_gguf(&path) {
+        return Err(format!("Model path {} is not a .gguf file.", path.display()));
+    }
+
+    unload(state.inner(), &app).await?;
+    state.set_model_path_override(Some(path));
+    Ok(start_model_load(state.inner(), &app))
+}
+
+/// Lists every GGUF found in the model directories.
+#[tauri::command]
+pub fn list_models(app: AppHandle) -> Vec<ModelEntry> {
+    model_registry::list_models(&app)
+}
+
+/// Prompt templates by name, with the version in effect and the override
+/// file it came from, if any.
+#[tauri::command]
+pub fn list_prompt_templates(app: AppHandle) -> Vec<PromptTemplateInfo> {
+    prompts::list(&app)
+}
+
+/// Built-in personas followed by the user's from the `personas` config
+/// directory.
+#[tauri::command]
+pub fn list_personas(app: AppHandle) -> Vec<Persona> {
+    personas::list_personas(&app)
+}
+
+/// Built-in sampling presets (`Steady`, `Dreamy`, `Deterministic`) followed
+/// by the ones saved with `save_sampling_preset`.
+#[tauri::command]
+pub fn list_sampling_presets(app: AppHandle) -> Vec<SamplingPreset> {
+    sampling_presets::list_presets(&app)
+}
+
+#[tauri::command]
+pub fn save_sampling_preset(
+    app: AppHandle,
+    name: String,
+    params: SamplingParams,
+) -> Result<SamplingPreset, String> {
+    params.validate().map_err(|errors| {
+        format!("Invalid sampling parameters: {}", format_field_errors(&errors))
+    })?;
+    sampling_presets::save_preset(&app, &name, params)
+}
+
+/// Persists `path` as the model to load on the next `init_model` or
+/// `reload_model`, ahead of `VEIL_MODEL_PATH`. Does not load it by itself.
+#[tauri::command]
+pub fn set_active_model(
+    state: State<'_, ModelManager>,
+    app: AppHandle,
+    path: String,
+) -> Result<ModelEntry, String> {
+    let entry = model_registry::set_active_model(&app, &PathBuf::from(path))?;
+    // A session-only `switch_model` choice would otherwise shadow the new default.
+    state.set_model_path_override(None);
+    Ok(entry)
+}
+
+/// Picks a GGUF with the native file dialog (or takes `path`), brings it into
+/// the app's models folder with `model:import` progress, then asks whether to
+/// load it. Passing `load` answers that question up front. Returns `None` when
+/// the picker is dismissed.
+#[tauri::command]
+pub async fn import_model(
+    state: State<'_, ModelManager>,
+    app: AppHandle,
+    path: Option<String>,
+    link: Option<bool>,
+    load: Option<bool>,
+) -> Result<Option<ModelEntry>, String> {
+    let source = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let picker_app = app.clone();
+            let picked = tauri::async_runtime::spawn_blocking(move || {
+                picker_app
+                    .dialog()
+                    .file()
+                    .set_title("Import a model")
+                    .add_filter("GGUF model", &["gguf"])
+                    .blocking_pick_file()
+            })
+            .await
+            .map_err(|error| format!("File picker task failed: {}", error))?;
+            match picked {
+                Some(file) => file.into_path().map_err(|error| error.to_string())?,
+                None => return Ok(None),
             }
         }
     };
 
-    match load_result {
-        Ok(Ok(backend)) => {
-            let model_size_bytes = backend.model_size_bytes;
-            let model_size_mb = (model_size_bytes as f32) / (1024.0 * 1024.0);
-            let model_path = backend.model_path.display().to_string();
-            state.set_backend(Arc::new(backend));
-            state.set_status(ModelStatus::Loaded {
-                model_path,
-                model_size_mb,
-                model_size_bytes,
-            });
-            emit_status(&app, state.get_status());
+    let import_app = app.clone();
+    let link = link.unwrap_or(false);
+    let entry = tauri::async_runtime::spawn_blocking(move || {
+        model_import::import_model_file(&import_app, &source, link)
+    })
+    .await
+    .map_err(|error| format!("Model import task failed: {}", error))??;
+
+    let load_now = match load {
+        Some(load) => load,
+        None => {
+            let prompt_app = app.clone();
+            let prompt = format!("{} was imported. Load it now?", entry.name);
+            tauri::async_runtime::spawn_blocking(move || {
+                prompt_app
+                    .dialog()
+                    .message(prompt)
+                    .title("Model imported")
+                    .kind(MessageDialogKind::Info)
+                    .buttons(MessageDialogButtons::OkCancelCustom(
+                        "Load now".to_string(),
+                        "Later".to_string(),
+                    ))
+                    .blocking_show()
+            })
+            .await
+            .unwrap_or(false)
         }
-        Ok(Err(message)) => {
-            state.set_status(ModelStatus::Error { message });
-            emit_status(&app, state.get_status());
+    };
+    if !load_now {
+        return Ok(Some(entry));
+    }
+
+    let entry = model_registry::set_active_model(&app, Path::new(&entry.path))?;
+    // The user asked for this model now, whatever `VEIL_MODEL_PATH` names.
+    state.set_model_path_override(Some(PathBuf::from(&entry.path)));
+    match unload(state.inner(), &app).await {
+        Ok(()) => {
+            start_model_load(state.inner(), &app);
         }
-        Err(error) => {
-            let message = format!("Model load task failed: {}", error);
-            state.set_status(ModelStatus::Error { message });
-            emit_status(&app, state.get_status());
+        // Still active for the next load; nothing to interrupt right now.
+        Err(error) => eprintln!("[Veil] model:import:load:deferred reason={}", error),
+    }
+    Ok(Some(entry))
+}
+
+const UNLOAD_DRAIN_GRACE: Duration = Duration::from_secs(10);
+
+async fn unload(state: &ModelManager, app: &AppHandle) -> Result<(), String> {
+    match state.get_status() {
+        ModelStatus::Loading { .. } => {
+            return Err("Model is still loading; unload it once loading has finished.".to_string())
         }
+        ModelStatus::Unloading => return Err("Model is already unloading.".to_string()),
+        ModelStatus::Unloaded | ModelStatus::EngineUnavailable { .. } => return Ok(()),
+        ModelStatus::Loaded { .. } | ModelStatus::Error { .. } | ModelStatus::Corrupt { .. } => {}
     }
+
+    state.set_status(ModelStatus::Unloading);
+    emit_status(app, state.get_status());
+    state.drain_generations(UNLOAD_DRAIN_GRACE).await;
+    // Nothing else holds the embedded backend once generations have drained,
+    // so this drops the mistral.rs model and releases its memory.
+    state.set_backend(Arc::new(StubBackend));
+    state.set_status(ModelStatus::Unloaded);
+    emit_status(app, state.get_status());
+    Ok(())
 }
 
+fn start_model_load(state: &ModelManager, app: &AppHandle) -> ModelStatus {
+    // A configured server takes precedence over the embedded engine.
+    #[cfg(feature = "openai")]
+    if let Some(config) = openai::OpenAiConfig::from_env() {
+        openai::connect(state, app, config);
+        return state.get_status();
+    }
+    #[cfg(feature = "mistral")]
+    model_loader::start(state, app);
+    #[cfg(not(feature = "mistral"))]
+    {
+        state.set_status(ModelStatus::EngineUnavailable {
+            message: ENGINE_UNAVAILABLE_MESSAGE.to_string(),
+        });
+        emit_status(app, state.get_status());
+    }
+    state.get_status()
+}
+
+#[cfg(not(feature = "mistral"))]
+const ENGINE_UNAVAILABLE_MESSAGE: &str =
+    "This build has no inference engine; readings come from the offline generator. Build with `--features mistral`, or with `--features openai` and VEIL_OPENAI_BASE_URL set.";
+
 #[tauri::command]
 pub fn model_status(state: State<'_, ModelManager>) -> ModelStatus {
     state.get_status()
 }
 
+/// Memory, swap and CPU figures, plus a pre-load memory check for `path`
+/// when given (otherwise the check from the last load, if any).
+#[tauri::command]
+pub async fn system_resources(
+    state: State<'_, ModelManager>,
+    path: Option<String>,
+) -> Result<SystemResources, String> {
+    let low_memory = state.low_memory();
+    let memory_check = match path {
+        Some(path) => Some(
+            tauri::async_runtime::spawn_blocking(move || {
+                memory::check_model_memory(&PathBuf::from(path), low_memory)
+            })
+            .await
+            .map_err(|error| format!("Memory check task failed: {}", error))??,
+        ),
+        None => state.memory_check(),
+    };
+    Ok(memory::system_resources(low_memory, memory_check))
+}
+
+/// Turns low-memory mode (one sequence, no prefix cache, CPU only) on or off
+/// for subsequent loads; call `reload_model` to apply it to the current one.
+#[tauri::command]
+pub fn set_low_memory_mode(state: State<'_, ModelManager>, enabled: bool) -> SystemResources {
+    state.set_low_memory(enabled);
+    memory::system_resources(enabled, state.memory_check())
+}
+
+/// GGUF header metadata for `path`, or for the loaded model when no path is
+/// given. Only the header is read, so this is cheap even for large files.
+#[tauri::command]
+pub async fn model_info(
+    state: State<'_, ModelManager>,
+    path: Option<String>,
+) -> Result<GgufInfo, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match state.get_status() {
+            ModelStatus::Loaded {
+                model_info: Some(info),
+                ..
+            } => return Ok(info),
+            ModelStatus::Loaded { model_path, .. } => PathBuf::from(model_path),
+            _ => return Err("No model is loaded.".to_string()),
+        },
+    };
+    tauri::async_runtime::spawn_blocking(move || inspect_gguf(&path))
+        .await
+        .map_err(|error| format!("Model inspection task failed: {}", error))?
+        .map_err(String::from)
+}
+
 #[tauri::command]
 pub async fn generate_horoscope(
     state: State<'_, ModelManager>,
+    app: AppHandle,
     profile: Profile,
     date: String,
     prompt: Option<String>,
-) -> Result<Reading, String> {
-    let request = ReadingRequest {
+    persona: Option<String>,
+    request_id: Option<String>,
+    priority: Option<GenerationPriority>,
+) -> Result<GenerationOutcome<Reading>, GenerationError> {
+    let started_at = Instant::now();
+    let persona = personas::resolve(&app, persona.as_deref())?;
+    let mut request = ReadingRequest {
         profile,
         date,
         prompt,
-        sampling: SamplingParams::default(),
+        sampling: persona.sampling.clone(),
+        persona,
     };
+    let sanitized = sanitize_request(&mut request);
+    let prompt_template = prepare_prompt(&app, &mut request, READING_TEMPLATE);
 
+    let generation = state.begin_generation(request_id);
+    let _permit = wait_for_slot(&state, &app, &generation, priority).await?;
     let (backend, source) = state.select_backend()?;
-    let result = backend
-        .generate_json(&request, &request.sampling)
-        .await
-        .and_then(|json| parse_reading_json(json, source));
-    match result {
-        Ok(reading) => Ok(reading),
+    let model = state.loaded_model();
+    let context = CallContext {
+        state: &state,
+        app: &app,
+        request_id: generation.request_id(),
+    };
+    let watchdog = generation.watchdog(generation_deadline(
+        READING_TIMEOUT_VAR,
+        READING_DEADLINE,
+        &request.sampling,
+        backend.as_ref(),
+    ));
+    let (result, attempts) = generate_parsed(
+        backend.as_ref(),
+        &request,
+        GenerationKind::Reading,
+        &context,
+        watchdog.token(),
+        |json| parse_reading_json(json, source),
+    )
+    .await;
+    let outcome = OutcomeBuilder {
+        started_at,
+        model,
+        attempts,
+        prompt_template,
+        sanitized,
+    };
+    match classify_timeout(result, &watchdog) {
+        Ok(reading) => Ok(outcome.build(reading, source, None)),
+        Err(BackendError::Cancelled) => Err(cancelled(generation.request_id())),
         Err(error) => {
             if matches!(source, ReadingSource::Model) {
                 eprintln!("Model inference failed, falling back to stub: {}", error);
-                Ok(generate_stub_reading(&request))
+                let reading = generate_stub_reading(&request);
+                Ok(outcome.build(reading, ReadingSource::Stub, Some(fallback_reason(&error))))
             } else {
-                Err(error)
+                Err(error.to_string().into())
             }
         }
     }
@@ -128,36 +400,90 @@ pub async fn generate_horoscope_stream(
     date: String,
     prompt: Option<String>,
     sampling: Option<SamplingParams>,
-) -> Result<Reading, String> {
-    let request = ReadingRequest {
+    seed: Option<u32>,
+    lenient: Option<bool>,
+    persona: Option<String>,
+    request_id: Option<String>,
+    priority: Option<GenerationPriority>,
+) -> Result<GenerationOutcome<Reading>, GenerationError> {
+    let started_at = Instant::now();
+    let persona = personas::resolve(&app, persona.as_deref())?;
+    let mut request = ReadingRequest {
         profile,
         date,
         prompt,
-        sampling: sampling.unwrap_or_default(),
+        sampling: checked_sampling(sampling, seed, &persona, lenient.unwrap_or(false))?,
+        persona,
     };
+    let sanitized = sanitize_request(&mut request);
+    let prompt_template = prepare_prompt(&app, &mut request, READING_TEMPLATE);
 
+    let generation = state.begin_generation(request_id);
+    let _permit = wait_for_slot(&state, &app, &generation, priority).await?;
     let (backend, source) = state.select_backend()?;
+    let model = state.loaded_model();
+    let context = CallContext {
+        state: &state,
+        app: &app,
+        request_id: generation.request_id(),
+    };
+    let watchdog = generation.watchdog(generation_deadline(
+        READING_TIMEOUT_VAR,
+        READING_DEADLINE,
+        &request.sampling,
+        backend.as_ref(),
+    ));
     emit_stream_event(&app, StreamEvent::Start);
-    let result = backend
-        .generate_json(&request, &request.sampling)
-        .await
-        .and_then(|json| parse_reading_json(json, source));
-    match result {
+    let (result, attempts) = generate_parsed(
+        backend.as_ref(),
+        &request,
+        GenerationKind::ReadingStream,
+        &context,
+        watchdog.token(),
+        |json| parse_reading_json(json, source),
+    )
+    .await;
+    let outcome = OutcomeBuilder {
+        started_at,
+        model,
+        attempts,
+        prompt_template,
+        sanitized,
+    };
+    match classify_timeout(result, &watchdog) {
         Ok(reading) => {
-            stream_message(&app, &reading.message).await;
             emit_stream_event(&app, StreamEvent::End);
-            Ok(reading)
+            Ok(outcome.build(reading, source, None))
+        }
+        Err(BackendError::Cancelled) => {
+            emit_stream_event(&app, StreamEvent::End);
+            Err(cancelled(generation.request_id()))
         }
         Err(error) => {
             if matches!(source, ReadingSource::Model) {
                 eprintln!("Model inference failed, falling back to stub: {}", error);
-                let reading = generate_stub_reading(&request);
-                stream_message(&app, &reading.message).await;
+                // Restart the stream so the UI drops any partial model output.
+                // The generation's own token is used here: a timeout cancelled
+                // only the watchdog's.
+                emit_stream_event(&app, StreamEvent::Start);
+                let fallback = stream_to_app(&app, &StubBackend, &request, generation.token())
+                    .await
+                    .and_then(|completion| {
+                        parse_reading_json(completion.content, ReadingSource::Stub)
+                            .map_err(BackendError::from)
+                    });
                 emit_stream_event(&app, StreamEvent::End);
-                Ok(reading)
+                match fallback {
+                    Err(BackendError::Cancelled) => Err(cancelled(generation.request_id())),
+                    fallback => {
+                        let reading = fallback.unwrap_or_else(|_| generate_stub_reading(&request));
+                        let reason = fallback_reason(&error);
+                        Ok(outcome.build(reading, ReadingSource::Stub, Some(reason)))
+                    }
+                }
             } else {
                 emit_stream_event(&app, StreamEvent::End);
-                Err(error)
+                Err(error.to_string().into())
             }
         }
     }
@@ -166,135 +492,518 @@ pub async fn generate_horoscope_stream(
 #[tauri::command]
 pub async fn generate_dashboard_payload(
     state: State<'_, ModelManager>,
+    app: AppHandle,
     profile: Profile,
     date: String,
     prompt: Option<String>,
     sampling: Option<SamplingParams>,
-) -> Result<String, String> {
-    let request = ReadingRequest {
+    seed: Option<u32>,
+    lenient: Option<bool>,
+    persona: Option<String>,
+    request_id: Option<String>,
+    priority: Option<GenerationPriority>,
+) -> Result<GenerationOutcome<DashboardPayload>, GenerationError> {
+    let started_at = Instant::now();
+    let persona = personas::resolve(&app, persona.as_deref())?;
+    let mut request = ReadingRequest {
         profile,
         date,
         prompt,
-        sampling: sampling.unwrap_or_default(),
+        sampling: checked_sampling(sampling, seed, &persona, lenient.unwrap_or(false))?,
+        persona,
     };
+    let sanitized = sanitize_request(&mut request);
+    let prompt_template = prepare_prompt(&app, &mut request, DASHBOARD_TEMPLATE);
 
+    let generation = state.begin_generation(request_id);
+    let _permit = wait_for_slot(&state, &app, &generation, priority).await?;
     let (backend, source) = state.select_backend()?;
-    match backend
-        .generate_dashboard_json(&request, &request.sampling)
-        .await
-    {
-        Ok(json) => Ok(json),
+    let model = state.loaded_model();
+    let context = CallContext {
+        state: &state,
+        app: &app,
+        request_id: generation.request_id(),
+    };
+    let watchdog = generation.watchdog(generation_deadline(
+        DASHBOARD_TIMEOUT_VAR,
+        DASHBOARD_DEADLINE,
+        &request.sampling,
+        backend.as_ref(),
+    ));
+    let (result, attempts) = generate_parsed(
+        backend.as_ref(),
+        &request,
+        GenerationKind::Dashboard,
+        &context,
+        watchdog.token(),
+        |json| parse_dashboard_payload(&json),
+    )
+    .await;
+    let outcome = OutcomeBuilder {
+        started_at,
+        model,
+        attempts,
+        prompt_template,
+        sanitized,
+    };
+    match classify_timeout(result, &watchdog) {
+        Ok(payload) => Ok(outcome.build(payload, source, None)),
+        Err(BackendError::Cancelled) => Err(cancelled(generation.request_id())),
         Err(error) => {
             if matches!(source, ReadingSource::Model) {
                 eprintln!("Model inference failed while generating dashboard JSON: {}", error);
-                let fallback = serde_json::to_string(&generate_stub_dashboard(&request))
-                    .map_err(|serialization| serialization.to_string())?;
-                Ok(fallback)
+                let fallback = generate_stub_dashboard(&request);
+                // The stub is hand-written; fail loudly if it ever drifts from the schema.
+                fallback
+                    .validate()
+                    .map_err(|errors| GenerationError::InvalidPayload { errors })?;
+                let reason = fallback_reason(&error);
+                Ok(outcome.build(fallback, ReadingSource::Stub, Some(reason)))
             } else {
-                Err(error)
+                Err(error.to_string().into())
             }
         }
     }
 }
 
-fn emit_status(app: &AppHandle, status: ModelStatus) {
-    let _ = app.emit("model:status", status);
+/// What every generation reports besides its payload; see `GenerationOutcome`.
+struct OutcomeBuilder {
+    started_at: Instant,
+    model: Option<String>,
+    attempts: u32,
+    prompt_template: Option<PromptStamp>,
+    sanitized: Vec<SanitizedField>,
 }
 
-fn emit_stream_event(app: &AppHandle, event: StreamEvent) {
-    let _ = app.emit("reading:stream", event);
+impl OutcomeBuilder {
+    fn build<T>(
+        self,
+        payload: T,
+        source: ReadingSource,
+        fallback_reason: Option<FallbackReason>,
+    ) -> GenerationOutcome<T> {
+        GenerationOutcome {
+            payload,
+            source: source.as_str().to_string(),
+            fallback: fallback_reason.is_some(),
+            fallback_reason,
+            model: self.model,
+            attempts: self.attempts,
+            latency_ms: self.started_at.elapsed().as_millis() as u64,
+            prompt_template: self.prompt_template,
+            sanitized: self.sanitized,
+        }
+    }
 }
 
-async fn stream_message(app: &AppHandle, message: &str) {
-    let chunk_size = 28;
-    for chunk in message.as_bytes().chunks(chunk_size) {
-        if let Ok(chunk_str) = std::str::from_utf8(chunk) {
-            emit_stream_event(
-                app,
-                StreamEvent::Chunk {
-                    chunk: chunk_str.to_string(),
-                },
-            );
+fn fallback_reason(error: &BackendError) -> FallbackReason {
+    let message = error.to_string();
+    match error {
+        BackendError::TimedOut(_) => FallbackReason::TimedOut { message },
+        BackendError::InvalidOutput(_) => FallbackReason::InvalidOutput { message },
+        BackendError::ContextOverflow(_) => FallbackReason::ContextOverflow { message },
+        BackendError::Cancelled | BackendError::Failed(_) => {
+            FallbackReason::InferenceFailed { message }
         }
-        tokio::time::sleep(Duration::from_millis(40)).await;
     }
 }
 
-fn parse_reading_json(json: String, source: ReadingSource) -> Result<Reading, String> {
-    let mut reading: Reading = serde_json::from_str(&json).map_err(|error| error.to_string())?;
-    reading.source = source.as_str().to_string();
-    Ok(reading)
+/// Token counts, latency and truncation totals for every model call since
+/// launch, plus the most recent calls.
+#[tauri::command]
+pub fn model_metrics(state: State<'_, ModelManager>) -> ModelMetrics {
+    state.metrics_report()
 }
 
-fn resolve_model_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let mut candidates: Vec<(String, PathBuf)> = Vec::new();
-    if let Ok(override_path) = std::env::var("VEIL_MODEL_PATH") {
-        candidates.push(("VEIL_MODEL_PATH".to_string(), PathBuf::from(override_path)));
+/// How many tokens `prompt` takes and how much of the loaded model's context
+/// window it leaves for output, so the pipeline can size prompts before
+/// sending them. Counts are estimated when no tokenizer is available, and
+/// include `persona`'s system prompt (the default persona's when unset).
+#[tauri::command]
+pub async fn estimate_tokens(
+    state: State<'_, ModelManager>,
+    app: AppHandle,
+    prompt: String,
+    persona: Option<String>,
+) -> Result<TokenEstimate, String> {
+    let persona = personas::resolve(&app, persona.as_deref())?;
+    let backend: Arc<dyn HoroscopeModelBackend> = match state.select_backend() {
+        Ok((backend, _)) => backend,
+        Err(_) => Arc::new(StubBackend),
+    };
+    Ok(budget::measure_prompt(backend.as_ref(), &system_prompt(&persona), &prompt).await)
+}
+
+/// Cancels the generation started with `request_id`, or every in-flight
+/// generation when no ID is given. Returns the IDs that were cancelled.
+#[tauri::command]
+pub fn cancel_generation(state: State<'_, ModelManager>, request_id: Option<String>) -> Vec<String> {
+    state.cancel_generation(request_id.as_deref())
+}
+
+const READING_TEMPLATE: &str = "reading";
+const DASHBOARD_TEMPLATE: &str = "dashboard";
+
+/// Fills in the prompt from `template` unless the caller sent its own.
+/// Returns the stamp of the template used, or `None` for a caller's prompt
+/// (or if rendering failed, leaving the backend's minimal prompt).
+fn prepare_prompt(
+    app: &AppHandle,
+    request: &mut ReadingRequest,
+    template: &str,
+) -> Option<PromptStamp> {
+    if request.prompt.is_some() {
+        return None;
     }
-    if let Ok(resource_dir) = app.path().resource_dir() {
-        candidates.push((
-            "resource_dir".to_string(),
-            resource_dir.join("veil.gguf"),
-        ));
-        candidates.push((
-            "resource_dir/resources".to_string(),
-            resource_dir.join("resources/veil.gguf"),
-        ));
+    match prompts::render(app, template, request) {
+        Ok(rendered) => {
+            request.prompt = Some(rendered.text);
+            Some(rendered.stamp)
+        }
+        Err(error) => {
+            eprintln!("[Veil] prompt:render:failed template={} error={}", template, error);
+            None
+        }
     }
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
-        candidates.push((
-            "app_data_dir".to_string(),
-            app_data_dir.join("veil.gguf"),
-        ));
+}
+
+/// Rejects out-of-range sampling parameters with one error per field, or
+/// with `lenient` clamps them into range and carries on. Without any, the
+/// persona's sampling applies. `seed`, when given, replaces the seed of
+/// whichever applies, so a caller can vary a reading without picking the
+/// rest of the parameters.
+fn checked_sampling(
+    sampling: Option<SamplingParams>,
+    seed: Option<u32>,
+    persona: &Persona,
+    lenient: bool,
+) -> Result<SamplingParams, GenerationError> {
+    let mut sampling = match sampling {
+        None => persona.sampling.clone(),
+        Some(sampling) if !lenient => {
+            sampling
+                .validate()
+                .map_err(|errors| GenerationError::InvalidSampling { errors })?;
+            sampling
+        }
+        Some(mut sampling) => {
+            let corrections = sampling.clamp();
+            if !corrections.is_empty() {
+                eprintln!("[Veil] model:sampling:clamped {}", format_field_errors(&corrections));
+            }
+            sampling
+        }
+    };
+    if seed.is_some() {
+        sampling.seed = seed;
     }
-    #[cfg(any(debug_assertions, dev))]
-    {
-        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        if let Some(project_dir) = manifest_dir.parent() {
-            candidates.push((
-                "project/resources".to_string(),
-                project_dir.join("resources/veil.gguf"),
-            ));
+    Ok(sampling)
+}
+
+/// Queues the generation behind any others the backend is already busy with.
+async fn wait_for_slot(
+    state: &ModelManager,
+    app: &AppHandle,
+    generation: &GenerationGuard,
+    priority: Option<GenerationPriority>,
+) -> Result<QueuePermit, GenerationError> {
+    state
+        .enqueue_generation(app, generation, priority.unwrap_or_default())
+        .await
+        .map_err(|error| match error {
+            BackendError::Cancelled => cancelled(generation.request_id()),
+            error => error.to_string().into(),
+        })
+}
+
+const READING_DEADLINE: Duration = Duration::from_secs(120);
+const READING_TIMEOUT_VAR: &str = "VEIL_READING_TIMEOUT_MS";
+// Dashboards generate several times as many tokens as a reading.
+const DASHBOARD_DEADLINE: Duration = Duration::from_secs(240);
+const DASHBOARD_TIMEOUT_VAR: &str = "VEIL_DASHBOARD_TIMEOUT_MS";
+
+/// How long the model may take, re-prompts included: `sampling.timeoutMs`
+/// when set, else the command's default (overridable through `env_var`),
+/// never more than the ceiling the backend was loaded with.
+fn generation_deadline(
+    env_var: &str,
+    default: Duration,
+    sampling: &SamplingParams,
+    backend: &dyn HoroscopeModelBackend,
+) -> Duration {
+    sampling
+        .timeout_ms
+        .filter(|ms| *ms > 0)
+        .or_else(|| std::env::var(env_var).ok().and_then(|value| value.parse().ok()))
+        .map(Duration::from_millis)
+        .unwrap_or(default)
+        .min(backend.generation_ceiling())
+}
+
+/// A cancel that came from the watchdog is a timeout, which falls back to the
+/// stub, rather than a cancel from the user, which does not.
+fn classify_timeout<T>(
+    result: Result<T, BackendError>,
+    watchdog: &Watchdog,
+) -> Result<T, BackendError> {
+    match result {
+        Err(BackendError::Cancelled) if watchdog.timed_out() => {
+            Err(BackendError::TimedOut(watchdog.deadline()))
         }
-        candidates.push((
-            "src-tauri/resources".to_string(),
-            manifest_dir.join("resources/veil.gguf"),
-        ));
+        result => result,
+    }
+}
+
+fn cancelled(request_id: &str) -> GenerationError {
+    GenerationError::Cancelled {
+        request_id: request_id.to_string(),
     }
+}
+
+pub(crate) fn emit_status(app: &AppHandle, status: ModelStatus) {
+    let _ = app.emit("model:status", status);
+}
+
+fn emit_stream_event(app: &AppHandle, event: StreamEvent) {
+    let _ = app.emit("reading:stream", event);
+}
 
-    for (_, candidate) in &candidates {
-        if candidate.exists() {
-            if candidate.is_file() {
-                return Ok(candidate.clone());
+/// Runs a streaming generation and forwards every decoded chunk to the
+/// `reading:stream` channel. All chunks are emitted before this returns.
+async fn stream_to_app(
+    app: &AppHandle,
+    backend: &dyn HoroscopeModelBackend,
+    request: &ReadingRequest,
+    cancel: &CancelToken,
+) -> Result<Completion, BackendError> {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let app_for_chunks = app.clone();
+    // Backends stream raw JSON; the event carries only the message text.
+    let forwarder = tauri::async_runtime::spawn(async move {
+        let mut message = MessageStream::default();
+        while let Some(json) = receiver.recv().await {
+            if let Some(chunk) = message.push(&json) {
+                emit_stream_event(&app_for_chunks, StreamEvent::Chunk { chunk });
             }
-            return Err(format!(
-                "Model path {} exists but is not a file.",
-                candidate.display()
-            ));
+        }
+    });
+    let result = backend
+        .stream_json(request, &request.sampling, sender, cancel)
+        .await;
+    let _ = forwarder.await;
+    result
+}
+
+/// Which backend entry point a generation goes through.
+#[derive(Clone, Copy)]
+enum GenerationKind {
+    Reading,
+    ReadingStream,
+    Dashboard,
+}
+
+impl GenerationKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            GenerationKind::Reading => "reading",
+            GenerationKind::ReadingStream => "reading_stream",
+            GenerationKind::Dashboard => "dashboard",
         }
     }
 
-    let searched = candidates
-        .iter()
-        .map(|(label, path)| {
-            let status = match std::fs::metadata(path) {
-                Ok(metadata) => {
-                    if metadata.is_file() {
-                        format!("file, {} bytes", metadata.len())
-                    } else {
-                        "exists, not a file".to_string()
-                    }
+    /// Output shorter than this cannot hold the complete JSON, so
+    /// `max_tokens` is never clamped below it.
+    fn min_output_tokens(self) -> u32 {
+        match self {
+            GenerationKind::Reading | GenerationKind::ReadingStream => 384,
+            GenerationKind::Dashboard => 1536,
+        }
+    }
+}
+
+/// Where `generate_parsed` reports the metrics of each model call.
+struct CallContext<'a> {
+    state: &'a ModelManager,
+    app: &'a AppHandle,
+    request_id: &'a str,
+}
+
+impl CallContext<'_> {
+    fn record(
+        &self,
+        kind: GenerationKind,
+        attempt: u32,
+        sampling: &SamplingParams,
+        usage: &CompletionUsage,
+        duration: Duration,
+    ) {
+        let time_to_first_token = usage.time_to_first_token;
+        // Fall back to our own measurement when the backend has none.
+        let tokens_per_second = usage.tokens_per_second.or_else(|| {
+            let decoding = duration.saturating_sub(time_to_first_token.unwrap_or_default());
+            let tokens = usage.completion_tokens?;
+            (decoding.as_secs_f32() > 0.0).then(|| tokens as f32 / decoding.as_secs_f32())
+        });
+        let truncated = usage.finish_reason.as_deref() == Some("length");
+        self.state.record_metrics(
+            self.app,
+            InferenceMetrics {
+                request_id: self.request_id.to_string(),
+                kind: kind.as_str().to_string(),
+                attempt,
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                time_to_first_token_ms: time_to_first_token.map(|ttft| ttft.as_millis() as u64),
+                duration_ms: duration.as_millis() as u64,
+                tokens_per_second,
+                prompt_tokens_per_second: usage.prompt_tokens_per_second,
+                prefix_cache_hit: usage.prefix_cache_hit,
+                finish_reason: usage.finish_reason.clone(),
+                max_tokens: sampling.max_tokens,
+                truncated,
+            },
+        );
+    }
+}
+
+const MAX_GENERATION_ATTEMPTS: u32 = 2;
+const REPROMPT_TEMPERATURE_FACTOR: f32 = 0.5;
+
+/// Generates and parses model output. Unparseable output is first repaired
+/// locally; if that fails the model is re-prompted once with the parse error
+/// at a lower temperature. Only then does the caller fall back to the stub.
+/// Returns the number of model calls made alongside the result.
+async fn generate_parsed<T>(
+    backend: &dyn HoroscopeModelBackend,
+    request: &ReadingRequest,
+    kind: GenerationKind,
+    context: &CallContext<'_>,
+    cancel: &CancelToken,
+    parse: impl Fn(String) -> Result<T, String>,
+) -> (Result<T, BackendError>, u32) {
+    let mut attempt_request = request.clone();
+    let mut attempts = 0;
+    let result = async {
+        loop {
+            attempts += 1;
+            let call_started_at = Instant::now();
+            let call = async {
+                // Re-prompts are longer, so each attempt is fitted again.
+                let min_output_tokens = kind.min_output_tokens();
+                budget::fit_max_tokens(backend, &mut attempt_request, min_output_tokens).await?;
+                call_backend(backend, &attempt_request, kind, context.app, attempts, cancel).await
+            };
+            let completion = match call.await {
+                Ok(completion) => completion,
+                Err(error) => {
+                    // Failed calls are counted too, so slow or broken ones
+                    // show up next to the ones that finished.
+                    let usage = CompletionUsage {
+                        finish_reason: Some(failure_finish_reason(&error, cancel).to_string()),
+                        ..CompletionUsage::default()
+                    };
+                    let elapsed = call_started_at.elapsed();
+                    context.record(kind, attempts, &attempt_request.sampling, &usage, elapsed);
+                    return Err(error);
                 }
-                Err(_) => "missing".to_string(),
             };
-            format!("{}: {} ({})", label, path.display(), status)
-        })
-        .collect::<Vec<_>>()
-        .join(", ");
-    Err(format!(
-        "Model file veil.gguf not found. Looked in: {}.",
-        searched
-    ))
+            context.record(
+                kind,
+                attempts,
+                &attempt_request.sampling,
+                &completion.usage,
+                call_started_at.elapsed(),
+            );
+            let error = match parse_with_repair(&completion.content, &parse) {
+                Ok(value) => {
+                    eprintln!("[Veil] model:parse:ok attempts={}", attempts);
+                    return Ok(value);
+                }
+                Err(error) => error,
+            };
+            eprintln!("[Veil] model:parse:failed attempt={} error={}", attempts, error);
+            if attempts >= MAX_GENERATION_ATTEMPTS {
+                return Err(BackendError::InvalidOutput(format!(
+                    "Model output could not be parsed after {} attempts: {}",
+                    attempts, error
+                )));
+            }
+            let original_prompt = request
+                .prompt
+                .clone()
+                .unwrap_or_else(|| build_fallback_prompt(request));
+            attempt_request.prompt = Some(build_reprompt(&original_prompt, &error));
+            attempt_request.sampling.temperature =
+                request.sampling.temperature * REPROMPT_TEMPERATURE_FACTOR;
+        }
+    }
+    .await;
+    (result, attempts)
+}
+
+/// Makes one model call for `kind`. A streamed retry (`attempt` past the
+/// first) resets the UI's buffer before it starts.
+async fn call_backend(
+    backend: &dyn HoroscopeModelBackend,
+    request: &ReadingRequest,
+    kind: GenerationKind,
+    app: &AppHandle,
+    attempt: u32,
+    cancel: &CancelToken,
+) -> Result<Completion, BackendError> {
+    match kind {
+        GenerationKind::Reading => backend.generate_json(request, &request.sampling, cancel).await,
+        GenerationKind::ReadingStream => {
+            if attempt > 1 {
+                emit_stream_event(app, StreamEvent::Start);
+            }
+            stream_to_app(app, backend, request, cancel).await
+        }
+        GenerationKind::Dashboard => {
+            backend
+                .generate_dashboard_json(request, &request.sampling, cancel)
+                .await
+        }
+    }
+}
+
+/// The `finishReason` recorded for a call that returned no completion. The
+/// watchdog cancels through `cancel`, so a timeout still arrives here as
+/// `Cancelled`.
+fn failure_finish_reason(error: &BackendError, cancel: &CancelToken) -> &'static str {
+    match error {
+        BackendError::Cancelled if cancel.reason() == Some(CancelReason::TimedOut) => "timeout",
+        BackendError::TimedOut(_) => "timeout",
+        BackendError::Cancelled => "cancelled",
+        BackendError::ContextOverflow(_) => "context_overflow",
+        BackendError::InvalidOutput(_) | BackendError::Failed(_) => "error",
+    }
+}
+
+/// Parses `raw` as-is, then retries on a locally repaired copy. The error from
+/// the repaired copy is reported since it describes what is actually wrong
+/// once fences and truncation are out of the way.
+fn parse_with_repair<T>(raw: &str, parse: &impl Fn(String) -> Result<T, String>) -> Result<T, String> {
+    let error = match parse(raw.to_string()) {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+    match repair_json(raw) {
+        Some(repaired) if repaired != raw => {
+            let result = parse(repaired);
+            if result.is_ok() {
+                eprintln!("[Veil] model:parse:repaired");
+            }
+            result
+        }
+        _ => Err(error),
+    }
+}
+
+fn parse_reading_json(json: String, source: ReadingSource) -> Result<Reading, String> {
+    let mut reading: Reading = serde_json::from_str(&json).map_err(|error| error.to_string())?;
+    reading.source = source.as_str().to_string();
+    Ok(reading)
 }
 
 #[tauri::command]
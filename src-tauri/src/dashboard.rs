@@ -0,0 +1,353 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::FieldError;
+
+// Typed mirror of the dashboard payload documented in docs/dashboard-schema.md.
+// Unknown keys are ignored on input so extra model chatter does not fail parsing.
+//
+// `meta`, `today` and `compatibility` are what the UI renders and what its
+// prompt asks for; they are required. The other sections are optional, and
+// checked only when present.
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardPayload {
+    pub meta: DashboardMeta,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tabs: Option<DashboardTabs>,
+    pub today: TodayPanel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosmic_weather: Option<CosmicWeather>,
+    pub compatibility: Compatibility,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal_ritual: Option<JournalRitual>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub week: Option<WeekOutlook>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub month: Option<MonthOutlook>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<YearOutlook>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardMeta {
+    #[serde(rename = "dateISO")]
+    pub date_iso: String,
+    pub locale_date_label: String,
+    #[serde(rename = "generatedAtISO")]
+    pub generated_at_iso: String,
+    pub sign: String,
+    pub name: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardTabs {
+    pub active_default: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodayPanel {
+    pub headline: String,
+    pub subhead: String,
+    pub theme: String,
+    pub energy_score: u32,
+    pub best_hours: Vec<HourWindow>,
+    pub ratings: Ratings,
+    pub lucky: Lucky,
+    pub do_dont: DoDont,
+    pub sections: Vec<Section>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct HourWindow {
+    pub label: String,
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Ratings {
+    pub love: u32,
+    pub work: u32,
+    pub money: u32,
+    pub health: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Lucky {
+    pub color: String,
+    pub number: u32,
+    pub symbol: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DoDont {
+    #[serde(rename = "do")]
+    pub action: String,
+    #[serde(rename = "dont")]
+    pub avoid: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Section {
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CosmicWeather {
+    pub moon: Moon,
+    pub transits: Vec<Transit>,
+    pub affects_today: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Moon {
+    pub phase: String,
+    pub sign: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Transit {
+    pub title: String,
+    pub tone: String,
+    pub meaning: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Compatibility {
+    pub best_flow_with: Vec<String>,
+    pub handle_gently_with: Vec<String>,
+    pub tips: CompatibilityTips,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CompatibilityTips {
+    pub conflict: String,
+    pub affection: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalRitual {
+    pub prompt: String,
+    pub starters: Vec<String>,
+    pub mantra: String,
+    pub ritual: String,
+    pub best_day_for_decisions: DecisionDay,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DecisionDay {
+    pub day_label: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekOutlook {
+    pub arc: WeekArc,
+    pub key_opportunity: String,
+    pub key_caution: String,
+    pub best_day_for: WeekBestDays,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WeekArc {
+    pub start: String,
+    pub midweek: String,
+    pub weekend: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WeekBestDays {
+    pub decisions: String,
+    pub conversations: String,
+    pub rest: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthOutlook {
+    pub theme: String,
+    pub key_dates: Vec<KeyDate>,
+    pub new_moon: NewMoon,
+    pub full_moon: FullMoon,
+    pub one_thing: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyDate {
+    pub date_label: String,
+    pub title: String,
+    pub note: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewMoon {
+    pub date_label: String,
+    pub intention: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FullMoon {
+    pub date_label: String,
+    pub release: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct YearOutlook {
+    pub headline: String,
+    pub quarters: Vec<Quarter>,
+    pub power_months: Vec<String>,
+    pub challenge_month: ChallengeMonth,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Quarter {
+    pub label: String,
+    pub focus: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ChallengeMonth {
+    pub month: String,
+    pub guidance: String,
+}
+
+/// Shared with the schema, which offers these as the only values.
+pub(crate) const SECTION_TITLES: [&str; 4] = ["Focus", "Relationships", "Action", "Reflection"];
+pub(crate) const QUARTER_LABELS: [&str; 4] = ["Q1", "Q2", "Q3", "Q4"];
+/// Shared with the schema and both prompts (Rust's and the UI's).
+pub(crate) const RATING_MIN: u32 = 0;
+pub(crate) const RATING_MAX: u32 = 5;
+
+/// Collects every constraint violation instead of stopping at the first one,
+/// so the caller (and a re-prompt) sees the full list.
+#[derive(Default)]
+struct Violations(Vec<FieldError>);
+
+impl Violations {
+    fn check(&mut self, ok: bool, field: impl Into<String>, message: impl Into<String>) {
+        if !ok {
+            self.0.push(FieldError {
+                field: field.into(),
+                message: message.into(),
+                min: None,
+                max: None,
+            });
+        }
+    }
+
+    fn count<T>(&mut self, items: &[T], expected: usize, field: &str) {
+        self.check(
+            items.len() == expected,
+            field,
+            format!("expected exactly {} items, found {}", expected, items.len()),
+        );
+    }
+
+    fn range(&mut self, value: u32, min: u32, max: u32, field: &str) {
+        if !(min..=max).contains(&value) {
+            self.0.push(FieldError {
+                field: field.to_string(),
+                message: format!("must be between {} and {}, found {}", min, max, value),
+                min: Some(min as f32),
+                max: Some(max as f32),
+            });
+        }
+    }
+
+    fn not_blank(&mut self, value: &str, field: &str) {
+        self.check(!value.trim().is_empty(), field, "must not be empty");
+    }
+}
+
+impl DashboardPayload {
+    /// Checks the constraints the UI relies on beyond what the types encode.
+    pub(crate) fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut violations = Violations::default();
+
+        violations.not_blank(&self.meta.date_iso, "meta.dateISO");
+        if let Some(tabs) = &self.tabs {
+            violations.check(
+                tabs.active_default == "today",
+                "tabs.activeDefault",
+                format!("must be \"today\", found \"{}\"", tabs.active_default),
+            );
+        }
+
+        let today = &self.today;
+        violations.not_blank(&today.headline, "today.headline");
+        violations.not_blank(&today.subhead, "today.subhead");
+        violations.range(today.energy_score, 0, 100, "today.energyScore");
+        violations.count(&today.best_hours, 2, "today.bestHours");
+        let ratings = [
+            (today.ratings.love, "today.ratings.love"),
+            (today.ratings.work, "today.ratings.work"),
+            (today.ratings.money, "today.ratings.money"),
+            (today.ratings.health, "today.ratings.health"),
+        ];
+        for (rating, field) in ratings {
+            violations.range(rating, RATING_MIN, RATING_MAX, field);
+        }
+        violations.count(&today.sections, SECTION_TITLES.len(), "today.sections");
+        for (index, (section, expected)) in today.sections.iter().zip(SECTION_TITLES).enumerate() {
+            violations.check(
+                section.title == expected,
+                format!("today.sections[{}].title", index),
+                format!("must be \"{}\", found \"{}\"", expected, section.title),
+            );
+        }
+
+        if let Some(month) = &self.month {
+            violations.count(&month.key_dates, 3, "month.keyDates");
+        }
+
+        if let Some(year) = &self.year {
+            violations.count(&year.quarters, QUARTER_LABELS.len(), "year.quarters");
+            let labels = year.quarters.iter().zip(QUARTER_LABELS).enumerate();
+            for (index, (quarter, expected)) in labels {
+                violations.check(
+                    quarter.label == expected,
+                    format!("year.quarters[{}].label", index),
+                    format!("must be \"{}\", found \"{}\"", expected, quarter.label),
+                );
+            }
+        }
+
+        if violations.0.is_empty() {
+            Ok(())
+        } else {
+            Err(violations.0)
+        }
+    }
+}
+
+/// Deserializes and validates a dashboard payload. Errors are flattened into
+/// one message so they can be logged and fed back into a re-prompt.
+pub(crate) fn parse_dashboard_payload(json: &str) -> Result<DashboardPayload, String> {
+    let payload: DashboardPayload = serde_json::from_str(json).map_err(|error| error.to_string())?;
+    payload
+        .validate()
+        .map_err(|errors| format_field_errors(&errors))?;
+    Ok(payload)
+}
+
+pub(crate) fn format_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
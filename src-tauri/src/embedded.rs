@@ -0,0 +1,569 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use mistralrs::{
+    Constraint,
+    DrySamplingParams,
+    Either,
+    GgufModelBuilder,
+    Model as MistralModel,
+    RequestBuilder,
+    Response,
+    SamplingParams as MistralSamplingParams,
+    StopTokens,
+    TextMessageRole,
+    TextMessages,
+    Usage,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::backends::{
+    build_fallback_prompt, generation_ceiling_from_env, BackendError, Completion, CompletionUsage,
+    system_prompt, HoroscopeModelBackend,
+};
+use crate::generation::CancelToken;
+use crate::gguf::{inspect_gguf, GgufError};
+use crate::integrity::{check_size, load_manifest, ChecksumVerifier, IntegrityError};
+use crate::load_progress::LoadReporter;
+use crate::prefix_cache::PrefixCacheProbe;
+use crate::schema::{dashboard_json_schema, reading_json_schema};
+use crate::types::{DryParams, GgufInfo, LoadStage, ReadingRequest, SamplingParams};
+
+// The mistral.rs engine. Only compiled with the `mistral` cargo feature; a
+// default build runs on `StubBackend` alone.
+
+/// Why `EmbeddedBackend::load` failed. Corruption is kept apart so the UI can
+/// tell the user to replace the file instead of showing a loader error.
+#[derive(Debug)]
+pub(crate) enum LoadError {
+    Failed(String),
+    Corrupt {
+        model_path: PathBuf,
+        error: IntegrityError,
+    },
+}
+
+impl LoadError {
+    fn corrupt(model_path: &Path, error: IntegrityError) -> Self {
+        LoadError::Corrupt {
+            model_path: model_path.to_path_buf(),
+            error,
+        }
+    }
+}
+
+impl From<String> for LoadError {
+    fn from(message: String) -> Self {
+        LoadError::Failed(message)
+    }
+}
+
+pub(crate) struct EmbeddedBackend {
+    pub(crate) model_path: PathBuf,
+    pub(crate) model_size_bytes: u64,
+    pub(crate) model_info: Option<GgufInfo>,
+    model: Arc<MistralModel>,
+    /// Whether generations are constrained to the JSON schemas in `schema.rs`.
+    /// Disable with `VEIL_MISTRALRS_CONSTRAINED=0` to compare against free-form output.
+    constrained: bool,
+    /// Generations sharing the model at once (`VEIL_MISTRALRS_MAX_CONCURRENT`,
+    /// default 1). Each one holds its own KV cache, so more than one mostly
+    /// buys memory pressure on a desktop.
+    max_concurrent: usize,
+    generation_ceiling: Duration,
+    /// Present when mistral.rs was built with a prefix cache.
+    prefix_cache: Option<PrefixCacheProbe>,
+}
+
+impl EmbeddedBackend {
+    /// Low-memory mode runs a single sequence on the CPU without a prefix
+    /// cache.
+    pub(crate) async fn load(
+        model_path: PathBuf,
+        low_memory: bool,
+        reporter: LoadReporter,
+    ) -> Result<Self, LoadError> {
+        let mut file = std::fs::File::open(&model_path).map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => {
+                format!("Model file {} is missing.", model_path.display())
+            }
+            std::io::ErrorKind::PermissionDenied => format!(
+                "Model file {} is not readable (permission denied).",
+                model_path.display()
+            ),
+            _ => format!(
+                "Failed to open model at {}: {}",
+                model_path.display(),
+                error
+            ),
+        })?;
+        let metadata = file.metadata().map_err(|error| {
+            format!(
+                "Failed to read model metadata at {}: {}",
+                model_path.display(),
+                error
+            )
+        })?;
+        let model_size_bytes = metadata.len();
+        let manifest = load_manifest(&model_path)?;
+        if let Some(manifest) = &manifest {
+            check_size(manifest, model_size_bytes)
+                .map_err(|error| LoadError::corrupt(&model_path, error))?;
+        }
+        // mistral.rs would reject a non-GGUF file too, but only after a slow
+        // read and with a far less specific message.
+        let model_info = match inspect_gguf(&model_path) {
+            Ok(info) => Some(info),
+            Err(error @ GgufError::NotGguf(_)) => return Err(String::from(error).into()),
+            Err(error) => {
+                eprintln!("[Veil] model:gguf:inspect:failed error={}", error);
+                None
+            }
+        };
+        let read_error = |error: std::io::Error| {
+            format!("Failed to read model at {}: {}", model_path.display(), error)
+        };
+        // Verification costs a full read of the file, even in low-memory mode.
+        // Without a manifest there is nothing to check, and mistral.rs reads
+        // the file itself, so it is not read beforehand.
+        if let Some(manifest) = manifest {
+            let mut verifier = ChecksumVerifier::new(manifest);
+            verify_model(&mut file, model_size_bytes, &mut verifier, &reporter)
+                .map_err(read_error)?;
+            verifier
+                .finish()
+                .map_err(|error| LoadError::corrupt(&model_path, error))?;
+        }
+
+        let model_dir = model_path
+            .parent()
+            .ok_or_else(|| format!("Model path {} has no parent directory.", model_path.display()))?;
+        let model_file = model_path
+            .file_name()
+            .ok_or_else(|| format!("Model path {} has no filename.", model_path.display()))?
+            .to_string_lossy()
+            .to_string();
+        let model_dir_str = model_dir.to_string_lossy().to_string();
+
+        // The memory check only counts system RAM, so low-memory mode keeps
+        // the weights there rather than on a GPU it never measured.
+        let force_cpu = low_memory
+            || std::env::var("VEIL_MISTRALRS_FORCE_CPU").ok().as_deref() == Some("1");
+        let enable_logging = std::env::var("VEIL_MISTRALRS_LOGGING").ok().as_deref() == Some("1");
+        let tok_model_id = std::env::var("VEIL_MISTRALRS_TOK_MODEL_ID").ok();
+        let chat_template = std::env::var("VEIL_MISTRALRS_CHAT_TEMPLATE").ok();
+        let constrained = std::env::var("VEIL_MISTRALRS_CONSTRAINED").ok().as_deref() != Some("0");
+        // mistral.rs keeps the KV cache of this many finished sequences and
+        // reuses one whose tokens start a new prompt, which skips most of the
+        // prompt processing when a reading is regenerated.
+        let prefix_cache_n = if low_memory {
+            None
+        } else {
+            std::env::var("VEIL_MISTRALRS_PREFIX_CACHE_N")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .or(Some(DEFAULT_PREFIX_CACHE_N))
+                .filter(|n| *n > 0)
+        };
+        let max_concurrent = if low_memory {
+            1
+        } else {
+            std::env::var("VEIL_MISTRALRS_MAX_CONCURRENT")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(1)
+        };
+
+        let build_with = |mut builder: GgufModelBuilder| {
+            if force_cpu {
+                builder = builder.with_force_cpu();
+            }
+            if enable_logging {
+                builder = builder.with_logging();
+            }
+            if low_memory {
+                builder = builder.with_max_num_seqs(1);
+            }
+            builder = builder.with_prefix_cache_n(prefix_cache_n);
+            if let Some(tok_model_id) = tok_model_id.clone() {
+                if !tok_model_id.trim().is_empty() {
+                    builder = builder.with_tok_model_id(tok_model_id);
+                }
+            }
+            if let Some(chat_template) = chat_template.clone() {
+                if !chat_template.trim().is_empty() {
+                    builder = builder.with_chat_template(chat_template);
+                }
+            }
+            builder
+        };
+
+        let attempt_primary = reporter
+            .track_build(
+                LoadStage::Building,
+                model_size_bytes,
+                build_with(GgufModelBuilder::new(model_dir_str.clone(), vec![model_file.clone()])).build(),
+            )
+            .await;
+
+        let model = match attempt_primary {
+            Ok(model) => model,
+            Err(primary_error) => {
+                // Some GGUF pipelines accept a fully-qualified path in the `files` list.
+                // When the standard <dir> + <filename> load fails, try again with the full path.
+                let full_path = model_path.to_string_lossy().to_string();
+                let attempt_full_path = reporter
+                    .track_build(
+                        LoadStage::BuildingFullPath,
+                        model_size_bytes,
+                        build_with(GgufModelBuilder::new("local".to_string(), vec![full_path])).build(),
+                    )
+                    .await;
+                match attempt_full_path {
+                    Ok(model) => model,
+                    Err(second_error) => {
+                        return Err(LoadError::Failed(format!(
+                            "Failed to load GGUF model. Primary error: {}. Full-path fallback error: {}",
+                            primary_error, second_error
+                        )));
+                    }
+                }
+            }
+        };
+        Ok(Self {
+            model_path,
+            model_size_bytes,
+            model_info,
+            model: Arc::new(model),
+            constrained,
+            max_concurrent,
+            generation_ceiling: generation_ceiling_from_env(),
+            prefix_cache: prefix_cache_n.map(|_| PrefixCacheProbe::default()),
+        })
+    }
+}
+
+const DEFAULT_PREFIX_CACHE_N: usize = 16;
+const VERIFY_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+const VERIFY_REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Feeds the whole GGUF through the checksum, reporting bytes as they arrive.
+/// The file is left in the OS page cache, so the mistral.rs build that
+/// follows mostly reads from memory.
+fn verify_model(
+    file: &mut std::fs::File,
+    total_bytes: u64,
+    verifier: &mut ChecksumVerifier,
+    reporter: &LoadReporter,
+) -> std::io::Result<()> {
+    let stage = LoadStage::Verifying;
+    let mut buffer = vec![0u8; VERIFY_CHUNK_BYTES];
+    let mut bytes_read = 0u64;
+    let mut last_report = Instant::now();
+    reporter.report(stage, 0.0, 0, total_bytes);
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        verifier.update(&buffer[..read]);
+        bytes_read += read as u64;
+        if last_report.elapsed() >= VERIFY_REPORT_INTERVAL {
+            last_report = Instant::now();
+            let fraction = bytes_read as f32 / total_bytes.max(1) as f32;
+            reporter.report(stage, fraction, bytes_read, total_bytes);
+        }
+    }
+    reporter.report(stage, 1.0, bytes_read, total_bytes);
+    Ok(())
+}
+
+/// Runs a chat request on a blocking thread. Cancelling `cancel` drops the
+/// in-flight request, which closes its response channel; mistral.rs then
+/// retires the sequence at the next decoding step instead of running it to
+/// `max_tokens`.
+async fn send_chat_request_blocking(
+    model: Arc<MistralModel>,
+    request_builder: RequestBuilder,
+    cancel: CancelToken,
+) -> Result<Completion, BackendError> {
+    let started_at = std::time::Instant::now();
+    let join = tauri::async_runtime::spawn_blocking(move || {
+        tauri::async_runtime::block_on(async {
+            tokio::select! {
+                response = model.send_chat_request(request_builder) => {
+                    response.map_err(|error| BackendError::Failed(error.to_string()))
+                }
+                _ = cancel.cancelled() => Err(BackendError::Cancelled),
+            }
+        })
+    });
+    let response = join
+        .await
+        .map_err(|error| format!("Model task join failed: {}", error))??;
+    let elapsed_ms = started_at.elapsed().as_millis();
+    eprintln!("[Veil] model:invoke:complete durationMs={}", elapsed_ms);
+    let choice = response
+        .choices
+        .first()
+        .ok_or_else(|| "Model returned empty content.".to_string())?;
+    let content = choice
+        .message
+        .content
+        .clone()
+        .ok_or_else(|| "Model returned empty content.".to_string())?;
+    let mut usage = completion_usage(&response.usage);
+    // Without streaming, prompt processing is the wait before the first token.
+    usage.time_to_first_token =
+        Duration::try_from_secs_f32(response.usage.total_prompt_time_sec).ok();
+    usage.finish_reason = Some(choice.finish_reason.clone());
+    Ok(Completion { content, usage })
+}
+
+fn completion_usage(usage: &Usage) -> CompletionUsage {
+    CompletionUsage {
+        prompt_tokens: Some(usage.prompt_tokens as u32),
+        completion_tokens: Some(usage.completion_tokens as u32),
+        tokens_per_second: Some(usage.avg_compl_tok_per_sec),
+        prompt_tokens_per_second: Some(usage.avg_prompt_tok_per_sec),
+        ..CompletionUsage::default()
+    }
+}
+
+async fn send_chat_request_streaming(
+    model: Arc<MistralModel>,
+    request_builder: RequestBuilder,
+    chunks: UnboundedSender<String>,
+    cancel: CancelToken,
+) -> Result<Completion, BackendError> {
+    let started_at = std::time::Instant::now();
+    let join = tauri::async_runtime::spawn_blocking(move || {
+        tauri::async_runtime::block_on(async {
+            let mut stream = model
+                .stream_chat_request(request_builder)
+                .await
+                .map_err(|error| BackendError::Failed(error.to_string()))?;
+            let mut content = String::new();
+            let mut usage = CompletionUsage::default();
+            loop {
+                // Dropping the stream on cancel closes the response channel,
+                // which stops mistral.rs from decoding further tokens.
+                let response = tokio::select! {
+                    response = stream.next() => response,
+                    _ = cancel.cancelled() => return Err(BackendError::Cancelled),
+                };
+                let Some(response) = response else {
+                    break;
+                };
+                match response {
+                    Response::Chunk(chunk) => {
+                        if let Some(choice) = chunk.choices.first() {
+                            // mistral.rs detokenizes incrementally, so every delta
+                            // is already a complete UTF-8 string.
+                            let delta = choice.delta.content.clone().unwrap_or_default();
+                            if !delta.is_empty() {
+                                if usage.time_to_first_token.is_none() {
+                                    usage.time_to_first_token = Some(started_at.elapsed());
+                                }
+                                content.push_str(&delta);
+                                let _ = chunks.send(delta);
+                            }
+                            if let Some(finish_reason) = &choice.finish_reason {
+                                usage.finish_reason = Some(finish_reason.clone());
+                            }
+                        }
+                        // The final chunk carries the usage for the whole request.
+                        if let Some(chunk_usage) = &chunk.usage {
+                            usage = CompletionUsage {
+                                time_to_first_token: usage.time_to_first_token,
+                                finish_reason: usage.finish_reason.take(),
+                                ..completion_usage(chunk_usage)
+                            };
+                        }
+                    }
+                    Response::Done(_) => break,
+                    Response::ModelError(message, _) => return Err(BackendError::Failed(message)),
+                    Response::InternalError(error) => {
+                        return Err(BackendError::Failed(error.to_string()))
+                    }
+                    Response::ValidationError(error) => {
+                        return Err(BackendError::Failed(error.to_string()))
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Completion { content, usage })
+        })
+    });
+    let completion = join
+        .await
+        .map_err(|error| format!("Model task join failed: {}", error))??;
+    let elapsed_ms = started_at.elapsed().as_millis();
+    eprintln!("[Veil] model:stream:complete durationMs={}", elapsed_ms);
+    if completion.content.is_empty() {
+        return Err(BackendError::Failed("Model returned empty content.".to_string()));
+    }
+    Ok(completion)
+}
+
+fn to_mistral_sampling_params(params: &SamplingParams) -> MistralSamplingParams {
+    let stop_toks = if params.stop.is_empty() {
+        None
+    } else {
+        Some(StopTokens::Seqs(params.stop.clone()))
+    };
+
+    MistralSamplingParams {
+        temperature: Some(params.temperature as f64),
+        top_k: Some(params.top_k as usize),
+        top_p: Some(params.top_p as f64),
+        min_p: params.min_p.map(f64::from),
+        top_n_logprobs: 0,
+        frequency_penalty: params.frequency_penalty,
+        presence_penalty: params.presence_penalty,
+        repetition_penalty: Some(params.repeat_penalty),
+        stop_toks,
+        max_len: Some(params.max_tokens as usize),
+        logits_bias: params.logit_bias.clone(),
+        n_choices: 1,
+        dry_params: params.dry.as_ref().and_then(to_mistral_dry_params),
+    }
+}
+
+fn to_mistral_dry_params(dry: &DryParams) -> Option<DrySamplingParams> {
+    DrySamplingParams::new_with_defaults(
+        dry.multiplier,
+        dry.sequence_breakers.clone(),
+        dry.base,
+        dry.allowed_length.map(|length| length as usize),
+    )
+    .map_err(|error| eprintln!("[Veil] model:sampling:dry:invalid error={}", error))
+    .ok()
+}
+
+impl EmbeddedBackend {
+    /// Sends one chat request, streaming into `chunks` when given.
+    async fn send(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        schema: serde_json::Value,
+        chunks: Option<UnboundedSender<String>>,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        // Prefer the prompt built by the TypeScript pipeline, which includes
+        // strict schema and UI style rules. It keeps per-request details at
+        // the end so the preamble stays a cacheable prefix.
+        let prompt = request
+            .prompt
+            .clone()
+            .unwrap_or_else(|| build_fallback_prompt(request));
+        let system_prompt = system_prompt(&request.persona);
+
+        let request_builder = RequestBuilder::new()
+            .add_message(TextMessageRole::System, system_prompt)
+            .add_message(TextMessageRole::User, prompt)
+            .set_sampling(to_mistral_sampling_params(sampling));
+        eprintln!("[Veil] model:invoke:start constrained={}", self.constrained);
+        let request_builder = if self.constrained {
+            request_builder.set_constraint(Constraint::JsonSchema(schema))
+        } else {
+            request_builder
+        };
+
+        let mut completion = match chunks {
+            Some(chunks) => {
+                send_chat_request_streaming(
+                    self.model.clone(),
+                    request_builder,
+                    chunks,
+                    cancel.clone(),
+                )
+                .await?
+            }
+            None => {
+                send_chat_request_blocking(self.model.clone(), request_builder, cancel.clone())
+                    .await?
+            }
+        };
+        // mistral.rs does not say when it reused a cached prefix; the probe
+        // judges it from the prompt throughput it measured.
+        let usage = &mut completion.usage;
+        if let (Some(probe), Some(prompt_tokens), Some(tokens_per_second)) = (
+            &self.prefix_cache,
+            usage.prompt_tokens,
+            usage.prompt_tokens_per_second,
+        ) {
+            usage.prefix_cache_hit = probe.observe(prompt_tokens, tokens_per_second);
+        }
+        Ok(completion)
+    }
+}
+
+#[async_trait]
+impl HoroscopeModelBackend for EmbeddedBackend {
+    async fn generate_json(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        self.send(request, sampling, reading_json_schema(), None, cancel).await
+    }
+
+    async fn stream_json(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        chunks: UnboundedSender<String>,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        self.send(request, sampling, reading_json_schema(), Some(chunks), cancel).await
+    }
+
+    async fn generate_dashboard_json(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        self.send(request, sampling, dashboard_json_schema(), None, cancel).await
+    }
+
+    fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    fn generation_ceiling(&self) -> Duration {
+        self.generation_ceiling
+    }
+
+    /// The context length the GGUF was trained with, which mistral.rs
+    /// sizes its KV cache to.
+    fn context_length(&self) -> Option<u32> {
+        self.model_info
+            .as_ref()
+            .and_then(|info| info.context_length)
+            .and_then(|tokens| u32::try_from(tokens).ok())
+    }
+
+    async fn count_prompt_tokens(&self, system_prompt: &str, prompt: &str) -> Option<u32> {
+        let messages = TextMessages::new()
+            .add_message(TextMessageRole::System, system_prompt)
+            .add_message(TextMessageRole::User, prompt);
+        match self
+            .model
+            .tokenize(Either::Left(messages), None, true, true, None)
+            .await
+        {
+            Ok(tokens) => u32::try_from(tokens.len()).ok(),
+            Err(error) => {
+                eprintln!("[Veil] model:tokenize:failed error={}", error);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::async_runtime::JoinHandle;
+use tokio::sync::watch;
+
+/// Why a generation was stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CancelReason {
+    /// `cancel_generation`, a superseding request, or an unload.
+    Requested,
+    /// The watchdog's deadline passed first.
+    TimedOut,
+}
+
+/// Cooperative cancellation flag shared between a command and the model task
+/// it spawned. Cloning is cheap; all clones observe the same flag. A child
+/// token (see `Watchdog`) is also cancelled by its parent, but not the reverse.
+#[derive(Clone)]
+pub struct CancelToken {
+    sender: Arc<watch::Sender<Option<CancelReason>>>,
+    parent: Option<Arc<CancelToken>>,
+}
+
+impl CancelToken {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = watch::channel(None);
+        Self {
+            sender: Arc::new(sender),
+            parent: None,
+        }
+    }
+
+    fn child(&self) -> Self {
+        Self {
+            parent: Some(Arc::new(self.clone())),
+            ..Self::new()
+        }
+    }
+
+    pub(crate) fn cancel(&self) {
+        self.cancel_with(CancelReason::Requested);
+    }
+
+    /// Cancels with `reason`, unless the token was already cancelled; the
+    /// first reason sticks.
+    pub(crate) fn cancel_with(&self, reason: CancelReason) {
+        self.sender.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(reason);
+            true
+        });
+    }
+
+    pub(crate) fn reason(&self) -> Option<CancelReason> {
+        let own = *self.sender.borrow();
+        own.or_else(|| self.parent.as_ref().and_then(|parent| parent.reason()))
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.reason().is_some()
+    }
+
+    /// Resolves once this token or its parent has been cancelled.
+    pub(crate) async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this only returns on cancel.
+        let own = receiver.wait_for(|reason| reason.is_some());
+        match &self.parent {
+            Some(parent) => {
+                tokio::select! {
+                    _ = own => {}
+                    _ = Box::pin(parent.cancelled()) => {}
+                }
+            }
+            None => {
+                let _ = own.await;
+            }
+        }
+    }
+}
+
+/// Tracks in-flight generations by request ID so they can be cancelled from
+/// another command, and counts them so a model unload can wait for them.
+#[derive(Clone)]
+pub(crate) struct GenerationRegistry {
+    active: Arc<Mutex<HashMap<String, CancelToken>>>,
+    next_id: Arc<AtomicU64>,
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+impl Default for GenerationRegistry {
+    fn default() -> Self {
+        let (in_flight, _) = watch::channel(0);
+        Self {
+            active: Arc::default(),
+            next_id: Arc::default(),
+            in_flight: Arc::new(in_flight),
+        }
+    }
+}
+
+impl GenerationRegistry {
+    /// Registers a generation under `request_id`, or a fresh ID when the caller
+    /// did not provide one. The entry is removed when the guard is dropped.
+    pub(crate) fn begin(&self, request_id: Option<String>) -> GenerationGuard {
+        let request_id = request_id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| format!("gen-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1));
+        let token = CancelToken::new();
+        self.in_flight.send_modify(|count| *count += 1);
+        if let Ok(mut active) = self.active.lock() {
+            // A reused ID supersedes the previous generation with that ID.
+            if let Some(previous) = active.insert(request_id.clone(), token.clone()) {
+                previous.cancel();
+            }
+        }
+        GenerationGuard {
+            request_id,
+            token,
+            registry: self.clone(),
+        }
+    }
+
+    /// Cancels the generation with `request_id`, or every in-flight generation
+    /// when no ID is given. Returns the IDs that were signalled.
+    pub(crate) fn cancel(&self, request_id: Option<&str>) -> Vec<String> {
+        let Ok(active) = self.active.lock() else {
+            return Vec::new();
+        };
+        active
+            .iter()
+            .filter(|(id, _)| request_id.map_or(true, |wanted| wanted == id.as_str()))
+            .map(|(id, token)| {
+                token.cancel();
+                id.clone()
+            })
+            .collect()
+    }
+
+    /// Resolves once no generation is in flight.
+    pub(crate) async fn idle(&self) {
+        let mut receiver = self.in_flight.subscribe();
+        let _ = receiver.wait_for(|count| *count == 0).await;
+    }
+
+    fn finish(&self, request_id: &str, token: &CancelToken) {
+        self.in_flight
+            .send_modify(|count| *count = count.saturating_sub(1));
+        if let Ok(mut active) = self.active.lock() {
+            let is_same = active
+                .get(request_id)
+                .map(|current| Arc::ptr_eq(&current.sender, &token.sender))
+                .unwrap_or(false);
+            if is_same {
+                active.remove(request_id);
+            }
+        }
+    }
+}
+
+pub(crate) struct GenerationGuard {
+    request_id: String,
+    token: CancelToken,
+    registry: GenerationRegistry,
+}
+
+impl GenerationGuard {
+    pub(crate) fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    pub(crate) fn token(&self) -> &CancelToken {
+        &self.token
+    }
+
+    /// Starts a watchdog that cancels the returned child token with
+    /// `CancelReason::TimedOut` once `deadline` passes. Cancelling the
+    /// generation still cancels the child; the generation's own token is
+    /// left usable for a fallback after a timeout.
+    pub(crate) fn watchdog(&self, deadline: Duration) -> Watchdog {
+        let token = self.token.child();
+        let watched = token.clone();
+        let request_id = self.request_id.clone();
+        let task = tauri::async_runtime::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(deadline) => {
+                    eprintln!(
+                        "[Veil] model:watchdog:timeout requestId={} deadlineMs={}",
+                        request_id,
+                        deadline.as_millis()
+                    );
+                    watched.cancel_with(CancelReason::TimedOut);
+                }
+                _ = watched.cancelled() => {}
+            }
+        });
+        Watchdog {
+            token,
+            deadline,
+            task,
+        }
+    }
+}
+
+impl Drop for GenerationGuard {
+    fn drop(&mut self) {
+        self.registry.finish(&self.request_id, &self.token);
+    }
+}
+
+/// A deadline on one generation. Dropping it stops the watchdog.
+pub(crate) struct Watchdog {
+    token: CancelToken,
+    deadline: Duration,
+    task: JoinHandle<()>,
+}
+
+impl Watchdog {
+    pub(crate) fn token(&self) -> &CancelToken {
+        &self.token
+    }
+
+    pub(crate) fn timed_out(&self) -> bool {
+        self.token.reason() == Some(CancelReason::TimedOut)
+    }
+
+    pub(crate) fn deadline(&self) -> Duration {
+        self.deadline
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
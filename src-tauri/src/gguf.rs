@@ -0,0 +1,525 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::types::GgufInfo;
+
+// Reader for the GGUF header: magic, version, metadata key/values and tensor
+// descriptors. Tensor data is never touched, so inspecting a multi-GB file
+// only reads the first few MB (mostly the tokenizer vocabulary, skipped).
+// Layout reference: https://github.com/ggerganov/ggml/blob/master/docs/gguf.md
+
+const GGUF_MAGIC: [u8; 4] = *b"GGUF";
+/// Strings longer than this are skipped rather than read; only a corrupt
+/// file (or an enormous embedded template) gets near it.
+const MAX_STRING_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_TENSOR_DIMS: u32 = 8;
+/// Arrays of arrays are legal but nothing real nests them deeply; each level
+/// recurses, so a crafted file could otherwise exhaust the stack.
+const MAX_ARRAY_DEPTH: u32 = 4;
+
+/// Why `inspect_gguf` failed. A file that is not GGUF at all is kept apart
+/// from one whose header is unreadable, which the loader may still try.
+#[derive(Debug)]
+pub(crate) enum GgufError {
+    NotGguf(PathBuf),
+    Invalid(String),
+}
+
+impl fmt::Display for GgufError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GgufError::NotGguf(path) => write!(f, "{} is not a GGUF file.", path.display()),
+            GgufError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for GgufError {
+    fn from(message: String) -> Self {
+        GgufError::Invalid(message)
+    }
+}
+
+impl From<GgufError> for String {
+    fn from(error: GgufError) -> Self {
+        error.to_string()
+    }
+}
+
+#[derive(Debug)]
+enum MetadataValue {
+    Unsigned(u64),
+    Signed(i64),
+    Text(String),
+    /// Floats, bools, arrays (token lists, merges) and oversized strings are
+    /// read past but not kept; nothing surfaced from the header needs them.
+    Skipped,
+}
+
+impl MetadataValue {
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            MetadataValue::Unsigned(value) => Some(*value),
+            MetadataValue::Signed(value) => u64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    fn as_text(&self) -> Option<String> {
+        match self {
+            MetadataValue::Text(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+struct HeaderReader<R: Read> {
+    inner: R,
+    version: u32,
+}
+
+impl<R: Read> HeaderReader<R> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut buffer = [0u8; N];
+        self.inner
+            .read_exact(&mut buffer)
+            .map_err(|error| format!("GGUF header is truncated: {}", error))?;
+        Ok(buffer)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.bytes()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.bytes()?))
+    }
+
+    /// Counts and lengths are 32-bit in GGUF v1 and 64-bit from v2 on.
+    fn count(&mut self) -> Result<u64, String> {
+        if self.version == 1 {
+            self.u32().map(u64::from)
+        } else {
+            self.u64()
+        }
+    }
+
+    fn skip(&mut self, bytes: u64) -> Result<(), String> {
+        let copied = std::io::copy(&mut (&mut self.inner).take(bytes), &mut std::io::sink())
+            .map_err(|error| format!("GGUF header is unreadable: {}", error))?;
+        if copied != bytes {
+            return Err("GGUF header is truncated.".to_string());
+        }
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<Option<String>, String> {
+        let length = self.count()?;
+        if length > MAX_STRING_BYTES {
+            self.skip(length)?;
+            return Ok(None);
+        }
+        let mut buffer = vec![0u8; length as usize];
+        self.inner
+            .read_exact(&mut buffer)
+            .map_err(|error| format!("GGUF header is truncated: {}", error))?;
+        Ok(Some(String::from_utf8_lossy(&buffer).into_owned()))
+    }
+
+    /// `depth` counts the arrays this value sits in.
+    fn value(&mut self, value_type: u32, depth: u32) -> Result<MetadataValue, String> {
+        let value = match value_type {
+            0 => MetadataValue::Unsigned(self.u8()? as u64),
+            1 => MetadataValue::Signed(self.u8()? as i8 as i64),
+            2 => MetadataValue::Unsigned(self.u16()? as u64),
+            3 => MetadataValue::Signed(self.u16()? as i16 as i64),
+            4 => MetadataValue::Unsigned(self.u32()? as u64),
+            5 => MetadataValue::Signed(self.u32()? as i32 as i64),
+            6 => {
+                self.skip(4)?;
+                MetadataValue::Skipped
+            }
+            7 => {
+                self.skip(1)?;
+                MetadataValue::Skipped
+            }
+            8 => self
+                .string()?
+                .map(MetadataValue::Text)
+                .unwrap_or(MetadataValue::Skipped),
+            9 => {
+                if depth >= MAX_ARRAY_DEPTH {
+                    return Err(format!(
+                        "GGUF metadata nests arrays more than {} deep; the header looks corrupt.",
+                        MAX_ARRAY_DEPTH
+                    ));
+                }
+                let item_type = self.u32()?;
+                let length = self.count()?;
+                match fixed_width(item_type) {
+                    Some(width) => self.skip(width.saturating_mul(length))?,
+                    None => {
+                        for _ in 0..length {
+                            self.value(item_type, depth + 1)?;
+                        }
+                    }
+                }
+                MetadataValue::Skipped
+            }
+            10 => MetadataValue::Unsigned(self.u64()?),
+            11 => MetadataValue::Signed(self.u64()? as i64),
+            12 => {
+                self.skip(8)?;
+                MetadataValue::Skipped
+            }
+            other => return Err(format!("GGUF metadata has unknown value type {}.", other)),
+        };
+        Ok(value)
+    }
+}
+
+/// Byte width of fixed-size metadata types, so arrays of them can be skipped
+/// in one step instead of element by element.
+fn fixed_width(value_type: u32) -> Option<u64> {
+    match value_type {
+        0 | 1 | 7 => Some(1),
+        2 | 3 => Some(2),
+        4 | 5 | 6 => Some(4),
+        10..=12 => Some(8),
+        _ => None,
+    }
+}
+
+/// Reads the GGUF header of `path` without loading any weights.
+pub(crate) fn inspect_gguf(path: &Path) -> Result<GgufInfo, GgufError> {
+    let file = File::open(path)
+        .map_err(|error| format!("Failed to open {}: {}", path.display(), error))?;
+    read_header(BufReader::new(file), path)
+}
+
+/// `path` only names the source in errors.
+fn read_header(inner: impl Read, path: &Path) -> Result<GgufInfo, GgufError> {
+    let mut reader = HeaderReader { inner, version: 0 };
+
+    let magic: [u8; 4] = reader.bytes()?;
+    if magic != GGUF_MAGIC {
+        return Err(GgufError::NotGguf(path.to_path_buf()));
+    }
+    reader.version = reader.u32()?;
+    if !(1..=3).contains(&reader.version) {
+        return Err(format!("Unsupported GGUF version {}.", reader.version).into());
+    }
+    let tensor_count = reader.count()?;
+    let metadata_count = reader.count()?;
+
+    let mut metadata: HashMap<String, MetadataValue> = HashMap::new();
+    for _ in 0..metadata_count {
+        let key = reader.string()?.unwrap_or_default();
+        let value_type = reader.u32()?;
+        let value = reader.value(value_type, 0)?;
+        metadata.insert(key, value);
+    }
+
+    let mut parameter_count: u64 = 0;
+    let mut elements_by_type: HashMap<u32, u64> = HashMap::new();
+    for _ in 0..tensor_count {
+        reader.string()?;
+        let dimensions = reader.u32()?;
+        if dimensions > MAX_TENSOR_DIMS {
+            return Err(format!(
+                "GGUF tensor has {} dimensions; the header looks corrupt.",
+                dimensions
+            )
+            .into());
+        }
+        let mut elements: u64 = 1;
+        for _ in 0..dimensions {
+            elements = elements.saturating_mul(reader.count()?);
+        }
+        let tensor_type = reader.u32()?;
+        let _offset = reader.u64()?;
+        parameter_count = parameter_count.saturating_add(elements);
+        *elements_by_type.entry(tensor_type).or_default() += elements;
+    }
+
+    let text = |key: &str| metadata.get(key).and_then(MetadataValue::as_text);
+    let architecture = text("general.architecture");
+    let arch_u64 = |suffix: &str| {
+        architecture
+            .as_ref()
+            .and_then(|arch| metadata.get(&format!("{}.{}", arch, suffix)))
+            .and_then(MetadataValue::as_u64)
+    };
+    let quantization = metadata
+        .get("general.file_type")
+        .and_then(MetadataValue::as_u64)
+        .and_then(file_type_name)
+        .or_else(|| {
+            // Older files omit `general.file_type`; report the type holding most weights.
+            elements_by_type
+                .iter()
+                .max_by_key(|(_, elements)| **elements)
+                .and_then(|(tensor_type, _)| tensor_type_name(*tensor_type))
+        })
+        .map(str::to_string);
+
+    Ok(GgufInfo {
+        version: reader.version,
+        tensor_count,
+        name: text("general.name"),
+        architecture: architecture.clone(),
+        parameter_count,
+        quantization,
+        context_length: arch_u64("context_length"),
+        block_count: arch_u64("block_count"),
+        embedding_length: arch_u64("embedding_length"),
+        head_count: arch_u64("attention.head_count"),
+        head_count_kv: arch_u64("attention.head_count_kv"),
+        chat_template: text("tokenizer.chat_template"),
+        tokenizer_model: text("tokenizer.ggml.model"),
+        license: text("general.license"),
+    })
+}
+
+/// Names for `general.file_type` (llama.cpp's `llama_ftype`).
+fn file_type_name(file_type: u64) -> Option<&'static str> {
+    let name = match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Names for per-tensor `ggml_type` values.
+fn tensor_type_name(tensor_type: u32) -> Option<&'static str> {
+    let name = match tensor_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        6 => "Q5_0",
+        7 => "Q5_1",
+        8 => "Q8_0",
+        9 => "Q8_1",
+        10 => "Q2_K",
+        11 => "Q3_K",
+        12 => "Q4_K",
+        13 => "Q5_K",
+        14 => "Q6_K",
+        15 => "Q8_K",
+        16 => "IQ2_XXS",
+        17 => "IQ2_XS",
+        18 => "IQ3_XXS",
+        19 => "IQ1_S",
+        20 => "IQ4_NL",
+        21 => "IQ3_S",
+        22 => "IQ2_S",
+        23 => "IQ4_XS",
+        28 => "F64",
+        29 => "IQ1_M",
+        30 => "BF16",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a header in memory, with counts as wide as `version` wants.
+    struct Header {
+        version: u32,
+        bytes: Vec<u8>,
+    }
+
+    impl Header {
+        fn new(version: u32, tensors: u64, metadata: u64) -> Self {
+            let mut header = Header {
+                version,
+                bytes: GGUF_MAGIC.to_vec(),
+            };
+            header.u32(version).count(tensors).count(metadata);
+            header
+        }
+
+        fn u32(&mut self, value: u32) -> &mut Self {
+            self.bytes.extend(value.to_le_bytes());
+            self
+        }
+
+        fn u64(&mut self, value: u64) -> &mut Self {
+            self.bytes.extend(value.to_le_bytes());
+            self
+        }
+
+        fn count(&mut self, value: u64) -> &mut Self {
+            if self.version == 1 {
+                self.u32(value as u32)
+            } else {
+                self.u64(value)
+            }
+        }
+
+        fn string(&mut self, value: &str) -> &mut Self {
+            self.count(value.len() as u64);
+            self.bytes.extend(value.as_bytes());
+            self
+        }
+
+        fn text(&mut self, key: &str, value: &str) -> &mut Self {
+            self.string(key).u32(8).string(value)
+        }
+
+        fn number(&mut self, key: &str, value: u32) -> &mut Self {
+            self.string(key).u32(4).u32(value)
+        }
+
+        fn tensor(&mut self, name: &str, dimensions: &[u64], tensor_type: u32) -> &mut Self {
+            self.string(name).u32(dimensions.len() as u32);
+            for dimension in dimensions {
+                self.count(*dimension);
+            }
+            self.u32(tensor_type).u64(0)
+        }
+
+        fn read(&self) -> Result<GgufInfo, GgufError> {
+            read_header(self.bytes.as_slice(), Path::new("test.gguf"))
+        }
+    }
+
+    fn invalid(result: Result<GgufInfo, GgufError>) -> String {
+        match result {
+            Err(GgufError::Invalid(message)) => message,
+            other => panic!("expected an invalid header, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_counts_as_wide_as_the_version() {
+        for version in [1, 3] {
+            let info = Header::new(version, 2, 3)
+                .text("general.architecture", "llama")
+                .number("llama.block_count", 32)
+                .number("general.file_type", 15)
+                .tensor("token_embd.weight", &[4096, 32000], 12)
+                .tensor("output_norm.weight", &[4096], 0)
+                .read()
+                .unwrap_or_else(|error| panic!("v{}: {}", version, error));
+            assert_eq!(info.version, version);
+            assert_eq!(info.tensor_count, 2);
+            assert_eq!(info.architecture.as_deref(), Some("llama"));
+            assert_eq!(info.block_count, Some(32));
+            assert_eq!(info.quantization.as_deref(), Some("Q4_K_M"));
+            assert_eq!(info.parameter_count, 4096 * 32000 + 4096);
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_commonest_tensor_type() {
+        let info = Header::new(3, 2, 0)
+            .tensor("blk.0.ffn_up.weight", &[64, 64], 8)
+            .tensor("output_norm.weight", &[64], 0)
+            .read()
+            .expect("a header");
+        assert_eq!(info.quantization.as_deref(), Some("Q8_0"));
+    }
+
+    #[test]
+    fn skips_oversized_strings_and_keeps_reading() {
+        let oversized = "x".repeat(MAX_STRING_BYTES as usize + 1);
+        let info = Header::new(3, 0, 2)
+            .text("tokenizer.chat_template", &oversized)
+            .text("general.name", "Veil")
+            .read()
+            .expect("a header");
+        assert_eq!(info.chat_template, None);
+        assert_eq!(info.name.as_deref(), Some("Veil"));
+    }
+
+    #[test]
+    fn skips_arrays_of_strings_and_of_arrays() {
+        let mut header = Header::new(3, 0, 3);
+        header.string("tokenizer.ggml.tokens").u32(9).u32(8).count(2);
+        header.string("<s>").string("</s>");
+        header.string("nested").u32(9).u32(9).count(1).u32(0).count(3);
+        header.bytes.extend([1, 2, 3]);
+        let info = header.text("general.name", "Veil").read().expect("a header");
+        assert_eq!(info.name.as_deref(), Some("Veil"));
+    }
+
+    #[test]
+    fn rejects_deeply_nested_arrays() {
+        let mut header = Header::new(3, 0, 1);
+        header.string("nested").u32(9);
+        for _ in 0..64 {
+            header.u32(9).count(1);
+        }
+        assert!(invalid(header.read()).contains("nests arrays"));
+    }
+
+    #[test]
+    fn reports_other_files_as_not_gguf() {
+        let result = read_header(&b"PK\x03\x04 not a model"[..], Path::new("notes.zip"));
+        assert!(matches!(result, Err(GgufError::NotGguf(path)) if path == Path::new("notes.zip")));
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        assert!(invalid(Header::new(4, 0, 0).read()).contains("version 4"));
+    }
+
+    #[test]
+    fn rejects_a_truncated_header() {
+        let mut header = Header::new(3, 0, 1);
+        header.text("general.name", "Veil");
+        header.bytes.truncate(header.bytes.len() - 2);
+        assert!(invalid(header.read()).contains("truncated"));
+    }
+
+    #[test]
+    fn rejects_tensors_with_too_many_dimensions() {
+        let message = invalid(Header::new(3, 1, 0).tensor("odd", &[1; 9], 0).read());
+        assert!(message.contains("9 dimensions"));
+    }
+}
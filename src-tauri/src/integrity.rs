@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Checksum manifests shipped next to a model as `<file>.manifest.json`, e.g.
+// `veil.gguf.manifest.json`. build.rs writes one for the bundled model from
+// the source copy, so a truncated copy in the bundle is caught at load time;
+// `import_model` writes one from the bytes it read while copying.
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ModelManifest {
+    /// Lowercase hex SHA-256 of the whole file.
+    pub(crate) sha256: String,
+    pub(crate) size_bytes: u64,
+}
+
+/// Why a model failed verification against its manifest.
+#[derive(Clone, Debug)]
+pub(crate) struct IntegrityError {
+    pub(crate) message: String,
+    pub(crate) expected_sha256: String,
+    pub(crate) actual_sha256: Option<String>,
+    pub(crate) expected_bytes: u64,
+    pub(crate) actual_bytes: u64,
+}
+
+pub(crate) fn manifest_path(model_path: &Path) -> PathBuf {
+    let mut file_name = model_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".manifest.json");
+    model_path.with_file_name(file_name)
+}
+
+/// The manifest next to `model_path`. A missing manifest is not an error
+/// (models added by hand have none); an unreadable one is.
+pub(crate) fn load_manifest(model_path: &Path) -> Result<Option<ModelManifest>, String> {
+    let path = manifest_path(model_path);
+    let json = match std::fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(format!("Unable to read {}: {}", path.display(), error)),
+    };
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|error| format!("Model manifest {} is invalid: {}", path.display(), error))
+}
+
+pub(crate) fn write_manifest(model_path: &Path, manifest: &ModelManifest) -> Result<(), String> {
+    let path = manifest_path(model_path);
+    let json = serde_json::to_string_pretty(manifest).map_err(|error| error.to_string())?;
+    std::fs::write(&path, json)
+        .map_err(|error| format!("Unable to write {}: {}", path.display(), error))
+}
+
+/// Cheap check before any hashing: a short file is truncated, full stop.
+pub(crate) fn check_size(manifest: &ModelManifest, actual_bytes: u64) -> Result<(), IntegrityError> {
+    if actual_bytes == manifest.size_bytes {
+        return Ok(());
+    }
+    let message = if actual_bytes < manifest.size_bytes {
+        format!(
+            "The model file is truncated: {} of {} bytes present. Copy it again.",
+            actual_bytes, manifest.size_bytes
+        )
+    } else {
+        format!(
+            "The model file is {} bytes but its manifest expects {}. It is not the file the manifest describes.",
+            actual_bytes, manifest.size_bytes
+        )
+    };
+    Err(IntegrityError {
+        message,
+        expected_sha256: manifest.sha256.clone(),
+        actual_sha256: None,
+        expected_bytes: manifest.size_bytes,
+        actual_bytes,
+    })
+}
+
+/// Incremental SHA-256 fed by the load's read pass, so verification costs no
+/// extra trip over the file.
+pub(crate) struct ChecksumVerifier {
+    manifest: ModelManifest,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+impl ChecksumVerifier {
+    pub(crate) fn new(manifest: ModelManifest) -> Self {
+        Self {
+            manifest,
+            hasher: Sha256::new(),
+            bytes: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+        self.bytes += chunk.len() as u64;
+    }
+
+    pub(crate) fn finish(self) -> Result<(), IntegrityError> {
+        let actual = to_hex(&self.hasher.finalize());
+        if actual.eq_ignore_ascii_case(self.manifest.sha256.trim()) {
+            return Ok(());
+        }
+        Err(IntegrityError {
+            message: "The model file is corrupt: its SHA-256 does not match the manifest. Copy it again."
+                .to_string(),
+            expected_sha256: self.manifest.sha256,
+            actual_sha256: Some(actual),
+            expected_bytes: self.manifest.size_bytes,
+            actual_bytes: self.bytes,
+        })
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -1,6 +1,37 @@
 mod backends;
+mod budget;
 mod commands;
+mod dashboard;
+#[cfg(feature = "mistral")]
+mod embedded;
+mod generation;
+mod gguf;
+// Checksum verification only runs as part of an embedded-engine load.
+#[cfg_attr(not(feature = "mistral"), allow(dead_code))]
+mod integrity;
+#[cfg(feature = "mistral")]
+mod load_progress;
+mod memory;
+mod message_stream;
+mod metrics;
+mod model_import;
+#[cfg(feature = "mistral")]
+mod model_loader;
 mod model_manager;
+mod model_registry;
+#[cfg(feature = "openai")]
+mod openai;
+mod personas;
+#[cfg(any(feature = "mistral", test))]
+mod prefix_cache;
+mod prompts;
+mod queue;
+mod repair;
+mod sampling;
+mod sampling_presets;
+mod sanitize;
+#[cfg(any(feature = "mistral", feature = "openai"))]
+mod schema;
 mod stub;
 mod types;
 
@@ -14,6 +45,7 @@ pub fn run() {
     tauri::Builder::default()
         .manage(ModelManager::new())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             if let Some(splash_window) = app.get_webview_window("splashscreen") {
                 let _ = splash_window.show();
@@ -40,9 +72,25 @@ pub fn run() {
             commands::close_splashscreen,
             commands::init_model,
             commands::model_status,
+            commands::model_info,
+            commands::model_metrics,
+            commands::estimate_tokens,
+            commands::system_resources,
+            commands::set_low_memory_mode,
+            commands::unload_model,
+            commands::reload_model,
+            commands::switch_model,
+            commands::list_models,
+            commands::set_active_model,
+            commands::list_personas,
+            commands::list_prompt_templates,
+            commands::list_sampling_presets,
+            commands::save_sampling_preset,
+            commands::import_model,
             commands::generate_horoscope,
             commands::generate_horoscope_stream,
-            commands::generate_dashboard_payload
+            commands::generate_dashboard_payload,
+            commands::cancel_generation
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,80 @@
+use std::future::Future;
+use std::time::Instant;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::types::{LoadStage, ModelStatus};
+
+/// Portion of the overall progress bar each stage covers. The two build
+/// attempts share a span: a fallback restarts the bar from the build start.
+/// Without a manifest to verify, the bar jumps straight to the build.
+fn stage_span(stage: LoadStage) -> (f32, f32) {
+    match stage {
+        LoadStage::Locating => (0.0, 0.02),
+        LoadStage::Verifying => (0.02, 0.4),
+        LoadStage::Building | LoadStage::BuildingFullPath => (0.4, 0.98),
+    }
+}
+
+/// Turns per-stage measurements into `ModelStatus::Loading` updates with an
+/// overall progress value and an ETA extrapolated from the time spent so far.
+#[derive(Clone)]
+pub(crate) struct LoadReporter {
+    sender: UnboundedSender<ModelStatus>,
+    started_at: Instant,
+}
+
+impl LoadReporter {
+    pub(crate) fn new(sender: UnboundedSender<ModelStatus>) -> Self {
+        Self {
+            sender,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Reports that `fraction` (0..=1) of `stage` is done.
+    pub(crate) fn report(&self, stage: LoadStage, fraction: f32, bytes_done: u64, bytes_total: u64) {
+        self.send(stage, fraction, bytes_done, bytes_total, false);
+    }
+
+    fn send(
+        &self,
+        stage: LoadStage,
+        fraction: f32,
+        bytes_done: u64,
+        bytes_total: u64,
+        indeterminate: bool,
+    ) {
+        let (start, end) = stage_span(stage);
+        let progress = start + (end - start) * fraction.clamp(0.0, 1.0);
+        let elapsed_ms = self.started_at.elapsed().as_millis() as f32;
+        // Too little progress makes the extrapolation meaningless, and an
+        // indeterminate stage gives it nothing to go on.
+        let eta_ms = (!indeterminate && progress >= 0.05)
+            .then(|| (elapsed_ms * (1.0 - progress) / progress) as u64);
+        let _ = self.sender.send(ModelStatus::Loading {
+            progress,
+            stage,
+            eta_ms,
+            bytes_done,
+            bytes_total,
+            indeterminate,
+        });
+    }
+
+    /// Drives `future` (a mistral.rs build), reporting `stage` as
+    /// indeterminate until it returns. mistral.rs offers no load callbacks,
+    /// and this process's memory says nothing once tensors go to a Metal or
+    /// CUDA device, so there is no fraction to report in between.
+    pub(crate) async fn track_build<F: Future>(
+        &self,
+        stage: LoadStage,
+        model_size_bytes: u64,
+        future: F,
+    ) -> F::Output {
+        self.send(stage, 0.0, 0, model_size_bytes, true);
+        let output = future.await;
+        self.report(stage, 1.0, model_size_bytes, model_size_bytes);
+        output
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use sysinfo::System;
+
+use crate::gguf::inspect_gguf;
+use crate::types::{GgufInfo, MemoryCheck, MemoryVerdict, SystemResources};
+
+// Rough pre-flight estimate of what loading a GGUF costs: the weights (the
+// file size, since quantised tensors are materialised as-is), a KV cache per
+// sequence kept alive, and a flat allowance for the runtime itself. It errs
+// on the generous side; the point is to catch "this cannot possibly fit", not
+// to predict the exact peak.
+
+const BYTES_PER_GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+/// Prompt plus `max_tokens` for the longest request the app sends.
+const CONTEXT_TOKENS: u64 = 8192;
+/// Sequences whose KV caches can be alive at once: overlapping generations
+/// plus prefixes mistral.rs keeps cached for reuse.
+const KV_SEQUENCES: u64 = 4;
+/// Low-memory mode runs one sequence on the CPU and keeps no prefix cache.
+const LOW_MEMORY_KV_SEQUENCES: u64 = 1;
+const KV_BYTES_PER_VALUE: u64 = 2;
+/// Used when the header lacks the attention shape: a fraction of the weights.
+const KV_FALLBACK_DIVISOR: u64 = 10;
+const RUNTIME_OVERHEAD_BYTES: u64 = 512 * 1024 * 1024;
+
+pub(crate) fn estimate_required_bytes(
+    model_size_bytes: u64,
+    info: Option<&GgufInfo>,
+    low_memory: bool,
+) -> u64 {
+    let sequences = if low_memory {
+        LOW_MEMORY_KV_SEQUENCES
+    } else {
+        KV_SEQUENCES
+    };
+    let kv_per_sequence = info
+        .and_then(kv_cache_bytes_per_sequence)
+        .unwrap_or(model_size_bytes / KV_FALLBACK_DIVISOR);
+    model_size_bytes
+        .saturating_add(kv_per_sequence.saturating_mul(sequences))
+        .saturating_add(RUNTIME_OVERHEAD_BYTES)
+}
+
+/// K and V for every layer, sized from the header's attention shape. The
+/// shape is whatever the file claims, so a product that overflows saturates
+/// rather than wrapping to a size that would seem to fit.
+fn kv_cache_bytes_per_sequence(info: &GgufInfo) -> Option<u64> {
+    let blocks = info.block_count?;
+    let embedding = info.embedding_length?;
+    let heads = info.head_count.filter(|heads| *heads > 0)?;
+    // Grouped-query models store fewer KV heads than attention heads.
+    let kv_heads = info.head_count_kv.unwrap_or(heads);
+    let head_dim = embedding / heads;
+    let tokens = info
+        .context_length
+        .map(|context| context.min(CONTEXT_TOKENS))
+        .unwrap_or(CONTEXT_TOKENS);
+    let bytes = [blocks, kv_heads, head_dim, tokens, KV_BYTES_PER_VALUE]
+        .into_iter()
+        .try_fold(2u64, u64::checked_mul)
+        .unwrap_or(u64::MAX);
+    Some(bytes)
+}
+
+/// Compares the estimate for `model_path` against what the system has free
+/// right now. Only fails when the file itself cannot be read.
+pub(crate) fn check_model_memory(model_path: &Path, low_memory: bool) -> Result<MemoryCheck, String> {
+    let model_size_bytes = std::fs::metadata(model_path)
+        .map_err(|error| {
+            format!(
+                "Failed to read model metadata at {}: {}",
+                model_path.display(),
+                error
+            )
+        })?
+        .len();
+    let info = inspect_gguf(model_path).ok();
+    let required_bytes = estimate_required_bytes(model_size_bytes, info.as_ref(), low_memory);
+    let low_memory_required_bytes = estimate_required_bytes(model_size_bytes, info.as_ref(), true);
+
+    let mut system = System::new();
+    system.refresh_memory();
+    let available_bytes = system.available_memory();
+    let free_swap_bytes = system.free_swap();
+
+    let verdict = if required_bytes <= available_bytes {
+        MemoryVerdict::Fits
+    } else if required_bytes <= available_bytes.saturating_add(free_swap_bytes) {
+        MemoryVerdict::Tight
+    } else {
+        MemoryVerdict::Insufficient
+    };
+    let low_memory_would_fit =
+        !low_memory && low_memory_required_bytes <= available_bytes.saturating_add(free_swap_bytes);
+    let message = match verdict {
+        MemoryVerdict::Fits => None,
+        MemoryVerdict::Tight => Some(format!(
+            "The model needs about {} of memory but only {} is free; it will lean on swap and may be very slow.{}",
+            gib(required_bytes),
+            gib(available_bytes),
+            if low_memory {
+                ""
+            } else {
+                " Low-memory mode lowers the requirement."
+            }
+        )),
+        MemoryVerdict::Insufficient => Some(format!(
+            "The model needs about {} of memory but only {} is free ({} including swap).{}",
+            gib(required_bytes),
+            gib(available_bytes),
+            gib(available_bytes.saturating_add(free_swap_bytes)),
+            if low_memory_would_fit {
+                " Turn on low-memory mode and reload to try anyway."
+            } else {
+                " Close other apps or pick a smaller model."
+            }
+        )),
+    };
+
+    Ok(MemoryCheck {
+        model_path: model_path.display().to_string(),
+        verdict,
+        required_bytes,
+        low_memory_required_bytes,
+        available_bytes,
+        free_swap_bytes,
+        low_memory_mode: low_memory,
+        message,
+    })
+}
+
+pub(crate) fn system_resources(low_memory: bool, memory_check: Option<MemoryCheck>) -> SystemResources {
+    let mut system = System::new();
+    system.refresh_memory();
+    SystemResources {
+        total_memory_bytes: system.total_memory(),
+        available_memory_bytes: system.available_memory(),
+        total_swap_bytes: system.total_swap(),
+        free_swap_bytes: system.free_swap(),
+        cpu_count: std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1),
+        low_memory_mode: low_memory,
+        memory_check,
+    }
+}
+
+fn gib(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / BYTES_PER_GIB)
+}
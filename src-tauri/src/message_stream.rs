@@ -0,0 +1,146 @@
+// Backends stream a reading as raw JSON, but `reading:stream` carries the
+// reading's message text: the UI writes every chunk straight into the
+// message element. `MessageStream` turns the former into the latter by
+// decoding the top-level `"message"` string as far as it has arrived.
+
+const MESSAGE_KEY: &str = "message";
+
+/// Accumulates streamed JSON and hands back the newly decoded part of the
+/// top-level `message` value after each chunk.
+#[derive(Default)]
+pub(crate) struct MessageStream {
+    json: String,
+    /// Bytes of the decoded message already handed out.
+    emitted: usize,
+}
+
+impl MessageStream {
+    /// Adds `chunk` and returns message text not returned before, if any.
+    pub(crate) fn push(&mut self, chunk: &str) -> Option<String> {
+        self.json.push_str(chunk);
+        let message = message_so_far(&self.json)?;
+        if message.len() <= self.emitted {
+            return None;
+        }
+        let fresh = message[self.emitted..].to_string();
+        self.emitted = message.len();
+        Some(fresh)
+    }
+}
+
+/// The decoded top-level `message` string of a possibly incomplete JSON
+/// object, up to the last character that has fully arrived. `None` until
+/// its opening quote has been seen.
+fn message_so_far(json: &str) -> Option<String> {
+    let bytes = json.as_bytes();
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' => {
+                let (text, end) = read_string(json, index + 1);
+                index = end? + 1;
+                if depth == 1 && text == MESSAGE_KEY {
+                    let rest = json[index..].trim_start();
+                    if let Some(value) = rest.strip_prefix(':') {
+                        let value = value.trim_start();
+                        if let Some(value) = value.strip_prefix('"') {
+                            return Some(read_string(value, 0).0);
+                        }
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    None
+}
+
+/// Decodes the JSON string body starting at `start` (just past its opening
+/// quote). Returns the text decoded so far and the index of the closing
+/// quote, or `None` for it when the string has not ended yet. An escape cut
+/// off by the end of the input is left out until the rest arrives.
+fn read_string(json: &str, start: usize) -> (String, Option<usize>) {
+    let mut text = String::new();
+    let mut chars = json[start..].char_indices();
+    while let Some((offset, character)) = chars.next() {
+        match character {
+            '"' => return (text, Some(start + offset)),
+            '\\' => {
+                let Some((_, escape)) = chars.next() else {
+                    break;
+                };
+                match escape {
+                    'n' => text.push('\n'),
+                    't' => text.push('\t'),
+                    'r' => text.push('\r'),
+                    'b' => text.push('\u{8}'),
+                    'f' => text.push('\u{c}'),
+                    'u' => {
+                        let hex: String =
+                            chars.by_ref().take(4).map(|(_, digit)| digit).collect();
+                        if hex.len() < 4 {
+                            break;
+                        }
+                        // Half of a surrogate pair decodes to nothing; models
+                        // rarely emit them for prose, so it is dropped.
+                        let decoded = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                        text.extend(decoded);
+                    }
+                    other => text.push(other),
+                }
+            }
+            other => text.push(other),
+        }
+    }
+    (text, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(chunks: &[&str]) -> String {
+        let mut stream = MessageStream::default();
+        chunks
+            .iter()
+            .filter_map(|chunk| stream.push(chunk))
+            .collect()
+    }
+
+    #[test]
+    fn emits_only_the_message_value() {
+        let json = concat!(
+            r#"{"date":"2026-01-01","title":"A message","#,
+            r#""message":"Soft focus today.","themes":["a","b","c"]}"#
+        );
+        assert_eq!(stream(&[json]), "Soft focus today.");
+    }
+
+    #[test]
+    fn survives_chunks_split_anywhere() {
+        let json = r#"{"title":"x","message":"Line one.\nSay \"yes\" — gently."}"#;
+        let chunks: Vec<String> = json.chars().map(String::from).collect();
+        let chunks: Vec<&str> = chunks.iter().map(String::as_str).collect();
+        assert_eq!(stream(&chunks), "Line one.\nSay \"yes\" \u{2014} gently.");
+    }
+
+    #[test]
+    fn ignores_nested_message_keys() {
+        let json = r#"{"meta":{"message":"nested"},"message":"top"}"#;
+        assert_eq!(stream(&[json]), "top");
+    }
+
+    #[test]
+    fn waits_for_the_value() {
+        let mut stream = MessageStream::default();
+        assert_eq!(stream.push(r#"{"message""#), None);
+        assert_eq!(stream.push(r#": "Hel"#).as_deref(), Some("Hel"));
+        assert_eq!(stream.push(r#"lo\"#), Some("lo".to_string()));
+        assert_eq!(stream.push(r#"n"}"#).as_deref(), Some("\n"));
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::types::{InferenceMetrics, MetricsSummary, ModelMetrics};
+
+const RECENT_CALLS: usize = 50;
+
+/// Collects the metrics of every model call since launch.
+#[derive(Clone, Default)]
+pub(crate) struct MetricsRegistry {
+    state: Arc<Mutex<MetricsState>>,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    by_kind: BTreeMap<String, KindTotals>,
+    recent: VecDeque<InferenceMetrics>,
+}
+
+#[derive(Default)]
+struct KindTotals {
+    summary: MetricsSummary,
+    ttft_ms_sum: u64,
+    ttft_samples: u32,
+    tokens_per_second_sum: f32,
+    tokens_per_second_samples: u32,
+}
+
+impl MetricsRegistry {
+    pub(crate) fn record(&self, app: &AppHandle, metrics: InferenceMetrics) {
+        eprintln!(
+            "[Veil] model:metrics kind={} attempt={} promptTokens={:?} completionTokens={:?} ttftMs={:?} tokensPerSecond={:?} prefixCacheHit={:?} finishReason={:?}",
+            metrics.kind,
+            metrics.attempt,
+            metrics.prompt_tokens,
+            metrics.completion_tokens,
+            metrics.time_to_first_token_ms,
+            metrics.tokens_per_second,
+            metrics.prefix_cache_hit,
+            metrics.finish_reason
+        );
+        if metrics.truncated {
+            eprintln!(
+                "[Veil] model:metrics:truncated kind={} maxTokens={}",
+                metrics.kind, metrics.max_tokens
+            );
+        }
+        let _ = app.emit("model:metrics", &metrics);
+
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let totals = state.by_kind.entry(metrics.kind.clone()).or_default();
+        let summary = &mut totals.summary;
+        summary.kind = metrics.kind.clone();
+        summary.calls += 1;
+        summary.truncated_calls += u32::from(metrics.truncated);
+        summary.prefix_cache_hits += u32::from(metrics.prefix_cache_hit == Some(true));
+        summary.prompt_tokens += u64::from(metrics.prompt_tokens.unwrap_or(0));
+        summary.completion_tokens += u64::from(metrics.completion_tokens.unwrap_or(0));
+        summary.max_completion_tokens = summary
+            .max_completion_tokens
+            .max(metrics.completion_tokens.unwrap_or(0));
+        if let Some(ttft_ms) = metrics.time_to_first_token_ms {
+            totals.ttft_ms_sum += ttft_ms;
+            totals.ttft_samples += 1;
+        }
+        if let Some(rate) = metrics.tokens_per_second {
+            totals.tokens_per_second_sum += rate;
+            totals.tokens_per_second_samples += 1;
+        }
+
+        if state.recent.len() == RECENT_CALLS {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(metrics);
+    }
+
+    pub(crate) fn report(&self) -> ModelMetrics {
+        let Ok(state) = self.state.lock() else {
+            return ModelMetrics {
+                summaries: Vec::new(),
+                recent: Vec::new(),
+            };
+        };
+        let summaries = state
+            .by_kind
+            .values()
+            .map(|totals| MetricsSummary {
+                mean_time_to_first_token_ms: (totals.ttft_samples > 0)
+                    .then(|| totals.ttft_ms_sum / u64::from(totals.ttft_samples)),
+                mean_tokens_per_second: (totals.tokens_per_second_samples > 0).then(|| {
+                    totals.tokens_per_second_sum / totals.tokens_per_second_samples as f32
+                }),
+                ..totals.summary.clone()
+            })
+            .collect();
+        ModelMetrics {
+            summaries,
+            recent: state.recent.iter().cloned().collect(),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+use crate::gguf::inspect_gguf;
+use crate::integrity::{load_manifest, to_hex, write_manifest, ModelManifest};
+use crate::model_registry;
+use crate::types::{ImportEvent, ModelEntry};
+
+const COPY_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Brings `source` into the import directory, emitting `model:import` events
+/// throughout. With `link` set, a hard link is tried first; it only works on
+/// the same volume, so a failure falls back to copying.
+pub(crate) fn import_model_file(
+    app: &AppHandle,
+    source: &Path,
+    link: bool,
+) -> Result<ModelEntry, String> {
+    match import(app, source, link) {
+        Ok((entry, linked)) => {
+            emit_import_event(
+                app,
+                ImportEvent::Imported {
+                    entry: entry.clone(),
+                    linked,
+                },
+            );
+            Ok(entry)
+        }
+        Err(message) => {
+            emit_import_event(
+                app,
+                ImportEvent::Failed {
+                    message: message.clone(),
+                },
+            );
+            Err(message)
+        }
+    }
+}
+
+fn import(app: &AppHandle, source: &Path, link: bool) -> Result<(ModelEntry, bool), String> {
+    if !source.is_file() {
+        return Err(format!("Model path {} is not a file.", source.display()));
+    }
+    if !model_registry::is_gguf(source) {
+        return Err(format!("Model path {} is not a .gguf file.", source.display()));
+    }
+    // Reads the header only; catches renamed non-GGUF files and unsupported versions.
+    inspect_gguf(source)?;
+
+    let directory = model_registry::import_directory(app)?;
+    std::fs::create_dir_all(&directory)
+        .map_err(|error| format!("Unable to create {}: {}", directory.display(), error))?;
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| format!("Model path {} has no filename.", source.display()))?;
+    let destination = directory.join(file_name);
+    if destination.exists() {
+        return Err(format!(
+            "A model named {} is already imported.",
+            file_name.to_string_lossy()
+        ));
+    }
+    let source_manifest = load_manifest(source)?;
+
+    let mut linked = false;
+    if link {
+        match std::fs::hard_link(source, &destination) {
+            Ok(()) => linked = true,
+            Err(error) => eprintln!(
+                "[Veil] model:import:link:failed error={} fallback=copy",
+                error
+            ),
+        }
+    }
+    if linked {
+        if let Some(manifest) = &source_manifest {
+            write_manifest(&destination, manifest)?;
+        }
+    } else {
+        let manifest = copy_with_progress(app, source, &destination)?;
+        if let Some(expected) = &source_manifest {
+            if !manifest.sha256.eq_ignore_ascii_case(expected.sha256.trim()) {
+                let _ = std::fs::remove_file(&destination);
+                return Err(format!(
+                    "{} does not match its checksum manifest; the source file is corrupt.",
+                    source.display()
+                ));
+            }
+        }
+        write_manifest(&destination, &manifest)?;
+    }
+
+    let entry = model_registry::model_entry_at(app, &destination)
+        .ok_or_else(|| format!("Unable to read model metadata at {}.", destination.display()))?;
+    Ok((entry, linked))
+}
+
+/// Copies through a `.part` file so an interrupted import never leaves a
+/// plausible-looking model behind, hashing the bytes on the way for the
+/// manifest.
+fn copy_with_progress(
+    app: &AppHandle,
+    source: &Path,
+    destination: &Path,
+) -> Result<ModelManifest, String> {
+    let partial = destination.with_extension("gguf.part");
+    let result = copy_into(app, source, &partial).and_then(|manifest| {
+        std::fs::rename(&partial, destination).map(|_| manifest).map_err(|error| {
+            format!("Unable to move {} into place: {}", partial.display(), error)
+        })
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+fn copy_into(app: &AppHandle, source: &Path, partial: &Path) -> Result<ModelManifest, String> {
+    let mut reader = File::open(source)
+        .map_err(|error| format!("Failed to open {}: {}", source.display(), error))?;
+    let mut writer = File::create(partial)
+        .map_err(|error| format!("Unable to create {}: {}", partial.display(), error))?;
+    let bytes_total = reader.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    let source_path = source.display().to_string();
+    let progress = |bytes_done: u64| ImportEvent::Copying {
+        source_path: source_path.clone(),
+        progress: bytes_done as f32 / bytes_total.max(1) as f32,
+        bytes_done,
+        bytes_total,
+    };
+
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_CHUNK_BYTES];
+    let mut bytes_done = 0u64;
+    let mut last_report = Instant::now();
+    emit_import_event(app, progress(0));
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|error| format!("Failed to read {}: {}", source.display(), error))?;
+        if read == 0 {
+            break;
+        }
+        writer
+            .write_all(&buffer[..read])
+            .map_err(|error| format!("Failed to write {}: {}", partial.display(), error))?;
+        hasher.update(&buffer[..read]);
+        bytes_done += read as u64;
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            emit_import_event(app, progress(bytes_done));
+        }
+    }
+    writer
+        .sync_all()
+        .map_err(|error| format!("Failed to flush {}: {}", partial.display(), error))?;
+    emit_import_event(app, progress(bytes_done));
+
+    Ok(ModelManifest {
+        sha256: to_hex(&hasher.finalize()),
+        size_bytes: bytes_done,
+    })
+}
+
+fn emit_import_event(app: &AppHandle, event: ImportEvent) {
+    let _ = app.emit("model:import", event);
+}
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::emit_status;
+use crate::embedded::{EmbeddedBackend, LoadError};
+use crate::load_progress::LoadReporter;
+use crate::memory;
+use crate::model_manager::ModelManager;
+use crate::model_registry;
+use crate::types::{LoadStage, MemoryVerdict, ModelStatus};
+
+// Background load of the embedded engine: locate the GGUF, check it fits in
+// memory, then build it while forwarding progress as `model:status` events.
+
+pub(crate) fn start(state: &ModelManager, app: &AppHandle) {
+    state.set_status(ModelStatus::Loading {
+        progress: 0.0,
+        stage: LoadStage::Locating,
+        eta_ms: None,
+        bytes_done: 0,
+        bytes_total: 0,
+        indeterminate: false,
+    });
+    emit_status(app, state.get_status());
+
+    tauri::async_runtime::spawn(run_model_load(state.clone(), app.clone()));
+}
+
+async fn run_model_load(state: ModelManager, app: AppHandle) {
+    let (progress_sender, mut progress_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let reporter = LoadReporter::new(progress_sender);
+    let app_for_load = app.clone();
+    let state_for_load = state.clone();
+    let model_path_override = state.model_path_override();
+    let low_memory = state.low_memory();
+    let mut load_handle = tauri::async_runtime::spawn_blocking(move || {
+        tauri::async_runtime::block_on(async {
+            let model_path = resolve_model_path(&app_for_load, model_path_override)?;
+            reporter.report(LoadStage::Locating, 1.0, 0, 0);
+            preflight_memory(&state_for_load, &app_for_load, &model_path, low_memory)?;
+            EmbeddedBackend::load(model_path, low_memory, reporter).await
+        })
+    });
+
+    let load_result = loop {
+        tokio::select! {
+            Some(status) = progress_receiver.recv() => {
+                state.set_status(status);
+                emit_status(&app, state.get_status());
+            }
+            result = &mut load_handle => {
+                break result;
+            }
+        }
+    };
+
+    match load_result {
+        Ok(Ok(backend)) => {
+            model_registry::record_model_used(&app, &backend.model_path);
+            let model_size_bytes = backend.model_size_bytes;
+            let model_size_mb = (model_size_bytes as f32) / (1024.0 * 1024.0);
+            let model_path = backend.model_path.display().to_string();
+            let model_info = backend.model_info.clone();
+            state.set_backend(Arc::new(backend));
+            state.set_status(ModelStatus::Loaded {
+                model_path,
+                model_size_mb,
+                model_size_bytes,
+                model_info,
+                backend: "embedded".to_string(),
+            });
+            emit_status(&app, state.get_status());
+        }
+        Ok(Err(LoadError::Failed(message))) => {
+            state.set_status(ModelStatus::Error { message });
+            emit_status(&app, state.get_status());
+        }
+        Ok(Err(LoadError::Corrupt { model_path, error })) => {
+            eprintln!(
+                "[Veil] model:integrity:failed path={} expected_bytes={} actual_bytes={}",
+                model_path.display(),
+                error.expected_bytes,
+                error.actual_bytes
+            );
+            state.set_status(ModelStatus::Corrupt {
+                model_path: model_path.display().to_string(),
+                message: error.message,
+                expected_sha256: error.expected_sha256,
+                actual_sha256: error.actual_sha256,
+                expected_bytes: error.expected_bytes,
+                actual_bytes: error.actual_bytes,
+            });
+            emit_status(&app, state.get_status());
+        }
+        Err(error) => {
+            let message = format!("Model load task failed: {}", error);
+            state.set_status(ModelStatus::Error { message });
+            emit_status(&app, state.get_status());
+        }
+    }
+}
+
+/// Refuses a load that cannot fit in free RAM plus swap, and warns about one
+/// that only fits by swapping. Both cases are also sent as `model:memory` so
+/// the UI can offer low-memory mode. `VEIL_SKIP_MEMORY_CHECK=1` disables it.
+fn preflight_memory(
+    state: &ModelManager,
+    app: &AppHandle,
+    model_path: &Path,
+    low_memory: bool,
+) -> Result<(), String> {
+    if std::env::var("VEIL_SKIP_MEMORY_CHECK").ok().as_deref() == Some("1") {
+        return Ok(());
+    }
+    let check = memory::check_model_memory(model_path, low_memory)?;
+    eprintln!(
+        "[Veil] model:memory verdict={:?} required={} available={} swap={} low_memory={}",
+        check.verdict,
+        check.required_bytes,
+        check.available_bytes,
+        check.free_swap_bytes,
+        low_memory
+    );
+    state.set_memory_check(Some(check.clone()));
+    if check.verdict == MemoryVerdict::Fits {
+        return Ok(());
+    }
+    let _ = app.emit("model:memory", &check);
+    match check.verdict {
+        MemoryVerdict::Insufficient => Err(check.message.unwrap_or_default()),
+        _ => Ok(()),
+    }
+}
+
+/// The session's `switch_model` choice, then the persisted active model, then
+/// `VEIL_MODEL_PATH`, then the bundled `veil.gguf`. A model picked in the app
+/// outranks the environment, which only sets the default.
+fn resolve_model_path(app: &AppHandle, model_path_override: Option<PathBuf>) -> Result<PathBuf, String> {
+    let mut candidates: Vec<(String, PathBuf)> = Vec::new();
+    if let Some(override_path) = model_path_override {
+        candidates.push(("switch_model".to_string(), override_path));
+    }
+    if let Some(active_path) = model_registry::active_model(app) {
+        candidates.push(("active_model".to_string(), active_path));
+    }
+    if let Ok(override_path) = std::env::var("VEIL_MODEL_PATH") {
+        candidates.push(("VEIL_MODEL_PATH".to_string(), PathBuf::from(override_path)));
+    }
+    for (label, directory) in model_registry::model_directories(app) {
+        candidates.push((label, directory.join("veil.gguf")));
+    }
+
+    for (_, candidate) in &candidates {
+        if candidate.exists() {
+            if candidate.is_file() {
+                return Ok(candidate.clone());
+            }
+            return Err(format!(
+                "Model path {} exists but is not a file.",
+                candidate.display()
+            ));
+        }
+    }
+
+    let searched = candidates
+        .iter()
+        .map(|(label, path)| {
+            let status = match std::fs::metadata(path) {
+                Ok(metadata) => {
+                    if metadata.is_file() {
+                        format!("file, {} bytes", metadata.len())
+                    } else {
+                        "exists, not a file".to_string()
+                    }
+                }
+                Err(_) => "missing".to_string(),
+            };
+            format!("{}: {} ({})", label, path.display(), status)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(format!(
+        "Model file veil.gguf not found. Looked in: {}.",
+        searched
+    ))
+}
@@ -1,7 +1,15 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::backends::{HoroscopeModelBackend, StubBackend};
-use crate::types::ModelStatus;
+use tauri::AppHandle;
+
+use crate::backends::{BackendError, HoroscopeModelBackend, StubBackend};
+use crate::generation::{GenerationGuard, GenerationRegistry};
+use crate::metrics::MetricsRegistry;
+use crate::queue::{InferenceQueue, QueuePermit};
+use crate::types::{GenerationPriority, InferenceMetrics, MemoryCheck, ModelMetrics, ModelStatus};
 
 #[derive(Clone, Copy, Debug)]
 #[allow(dead_code)]
@@ -23,6 +31,12 @@ impl ReadingSource {
 pub struct ModelManager {
     status: Arc<Mutex<ModelStatus>>,
     backend: Arc<Mutex<Arc<dyn HoroscopeModelBackend>>>,
+    generations: GenerationRegistry,
+    queue: InferenceQueue,
+    metrics: MetricsRegistry,
+    model_path_override: Arc<Mutex<Option<PathBuf>>>,
+    low_memory: Arc<AtomicBool>,
+    memory_check: Arc<Mutex<Option<MemoryCheck>>>,
 }
 
 impl ModelManager {
@@ -30,6 +44,14 @@ impl ModelManager {
         Self {
             status: Arc::new(Mutex::new(ModelStatus::Unloaded)),
             backend: Arc::new(Mutex::new(Arc::new(StubBackend))),
+            generations: GenerationRegistry::default(),
+            queue: InferenceQueue::default(),
+            metrics: MetricsRegistry::default(),
+            model_path_override: Arc::new(Mutex::new(None)),
+            low_memory: Arc::new(AtomicBool::new(
+                std::env::var("VEIL_LOW_MEMORY").ok().as_deref() == Some("1"),
+            )),
+            memory_check: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -49,11 +71,101 @@ impl ModelManager {
     }
 
     pub(crate) fn set_backend(&self, backend: Arc<dyn HoroscopeModelBackend>) {
+        self.queue.set_limit(backend.max_concurrent());
         if let Ok(mut guard) = self.backend.lock() {
             *guard = backend;
         }
     }
 
+    pub(crate) fn begin_generation(&self, request_id: Option<String>) -> GenerationGuard {
+        self.generations.begin(request_id)
+    }
+
+    /// Waits in the inference queue until the backend has room for another
+    /// generation.
+    pub(crate) async fn enqueue_generation(
+        &self,
+        app: &AppHandle,
+        generation: &GenerationGuard,
+        priority: GenerationPriority,
+    ) -> Result<QueuePermit, BackendError> {
+        self.queue
+            .acquire(app, generation.request_id(), priority, generation.token())
+            .await
+    }
+
+    /// Emits one model call's metrics on `model:metrics` and adds them to
+    /// the totals `model_metrics` reports.
+    pub(crate) fn record_metrics(&self, app: &AppHandle, metrics: InferenceMetrics) {
+        self.metrics.record(app, metrics);
+    }
+
+    pub(crate) fn metrics_report(&self) -> ModelMetrics {
+        self.metrics.report()
+    }
+
+    pub(crate) fn cancel_generation(&self, request_id: Option<&str>) -> Vec<String> {
+        self.generations.cancel(request_id)
+    }
+
+    /// Waits up to `grace` for in-flight generations to finish, then cancels
+    /// whatever is still running and waits for those to wind down.
+    pub(crate) async fn drain_generations(&self, grace: Duration) {
+        if tokio::time::timeout(grace, self.generations.idle()).await.is_err() {
+            let cancelled = self.generations.cancel(None);
+            eprintln!(
+                "[Veil] model:drain:timeout cancelled={}",
+                cancelled.join(",")
+            );
+            self.generations.idle().await;
+        }
+    }
+
+    #[cfg_attr(not(feature = "mistral"), allow(dead_code))]
+    pub(crate) fn model_path_override(&self) -> Option<PathBuf> {
+        self.model_path_override
+            .lock()
+            .ok()
+            .and_then(|path| path.clone())
+    }
+
+    pub(crate) fn set_model_path_override(&self, path: Option<PathBuf>) {
+        if let Ok(mut guard) = self.model_path_override.lock() {
+            *guard = path;
+        }
+    }
+
+    /// Whether the next load should trade throughput for a smaller footprint.
+    pub(crate) fn low_memory(&self) -> bool {
+        self.low_memory.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_low_memory(&self, enabled: bool) {
+        self.low_memory.store(enabled, Ordering::SeqCst);
+    }
+
+    pub(crate) fn memory_check(&self) -> Option<MemoryCheck> {
+        self.memory_check
+            .lock()
+            .ok()
+            .and_then(|check| check.clone())
+    }
+
+    #[cfg_attr(not(feature = "mistral"), allow(dead_code))]
+    pub(crate) fn set_memory_check(&self, check: Option<MemoryCheck>) {
+        if let Ok(mut guard) = self.memory_check.lock() {
+            *guard = check;
+        }
+    }
+
+    /// The path (or server and model name) of the loaded model, if any.
+    pub(crate) fn loaded_model(&self) -> Option<String> {
+        match self.get_status() {
+            ModelStatus::Loaded { model_path, .. } => Some(model_path),
+            _ => None,
+        }
+    }
+
     pub(crate) fn select_backend(&self) -> Result<(Arc<dyn HoroscopeModelBackend>, ReadingSource), String> {
         match self.get_status() {
             ModelStatus::Loaded { .. } => self
@@ -62,8 +174,11 @@ impl ModelManager {
                 .map(|backend| (backend.clone(), ReadingSource::Model))
                 .map_err(|_| "Unable to access loaded model backend.".to_string()),
             ModelStatus::Loading { .. } => Err("Model is still loading.".to_string()),
+            ModelStatus::Unloading => Err("Model is unloading.".to_string()),
             ModelStatus::Unloaded => Err("Model is not initialized.".to_string()),
-            ModelStatus::Error { message } => Err(message),
+            ModelStatus::Error { message }
+            | ModelStatus::EngineUnavailable { message }
+            | ModelStatus::Corrupt { message, .. } => Err(message),
         }
     }
 }
@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::types::ModelEntry;
+
+const REGISTRY_FILE: &str = "model_registry.json";
+
+/// What survives restarts: the model picked with `set_active_model` and when
+/// each model was last loaded successfully. Paths are stored canonicalised
+/// (see `registry_key`).
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryState {
+    active_model_path: Option<PathBuf>,
+    #[serde(default)]
+    last_used: BTreeMap<PathBuf, String>,
+}
+
+/// Directories searched for models, labelled for error messages. The bundled
+/// `veil.gguf` lives in one of these; any other `*.gguf` next to it is listed too.
+pub(crate) fn model_directories(app: &AppHandle) -> Vec<(String, PathBuf)> {
+    let mut directories: Vec<(String, PathBuf)> = Vec::new();
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        directories.push(("resource_dir".to_string(), resource_dir.clone()));
+        directories.push((
+            "resource_dir/resources".to_string(),
+            resource_dir.join("resources"),
+        ));
+    }
+    if let Ok(app_data_dir) = app.path().app_data_dir() {
+        directories.push(("app_data_dir".to_string(), app_data_dir.clone()));
+        directories.push(("app_data_dir/models".to_string(), app_data_dir.join("models")));
+    }
+    #[cfg(any(debug_assertions, dev))]
+    {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        if let Some(project_dir) = manifest_dir.parent() {
+            directories.push((
+                "project/resources".to_string(),
+                project_dir.join("resources"),
+            ));
+        }
+        directories.push((
+            "src-tauri/resources".to_string(),
+            manifest_dir.join("resources"),
+        ));
+    }
+    directories
+}
+
+/// Where `import_model` puts models; also one of the scanned directories.
+pub(crate) fn import_directory(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("models"))
+        .map_err(|error| format!("Unable to resolve app data directory: {}", error))
+}
+
+/// Scans the model directories (plus `VEIL_MODEL_PATH`) for `*.gguf` files.
+pub(crate) fn list_models(app: &AppHandle) -> Vec<ModelEntry> {
+    let state = load_state(app);
+    let mut paths: Vec<PathBuf> = Vec::new();
+    if let Ok(override_path) = std::env::var("VEIL_MODEL_PATH") {
+        paths.push(PathBuf::from(override_path));
+    }
+    for (_, directory) in model_directories(app) {
+        let Ok(entries) = std::fs::read_dir(&directory) else {
+            continue;
+        };
+        paths.extend(entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()));
+    }
+    if let Some(active) = &state.active_model_path {
+        paths.push(active.clone());
+    }
+
+    let mut seen = HashSet::new();
+    let mut models: Vec<ModelEntry> = paths
+        .into_iter()
+        .filter(|path| is_gguf(path) && path.is_file())
+        // The same file is often reachable through several candidate directories.
+        .filter(|path| seen.insert(registry_key(path)))
+        .filter_map(|path| model_entry(&path, &state))
+        .collect();
+    models.sort_by(|left, right| left.name.cmp(&right.name));
+    models
+}
+
+/// Persists `path` as the model the next `init_model` loads.
+pub(crate) fn set_active_model(app: &AppHandle, path: &Path) -> Result<ModelEntry, String> {
+    if !path.is_file() {
+        return Err(format!("Model path {} is not a file.", path.display()));
+    }
+    if !is_gguf(path) {
+        return Err(format!("Model path {} is not a .gguf file.", path.display()));
+    }
+    let mut state = load_state(app);
+    state.active_model_path = Some(registry_key(path));
+    save_state(app, &state)?;
+    model_entry(path, &state)
+        .ok_or_else(|| format!("Unable to read model metadata at {}.", path.display()))
+}
+
+/// The persisted active model, if it still exists on disk.
+#[cfg_attr(not(feature = "mistral"), allow(dead_code))]
+pub(crate) fn active_model(app: &AppHandle) -> Option<PathBuf> {
+    load_state(app)
+        .active_model_path
+        .filter(|path| path.is_file())
+}
+
+#[cfg_attr(not(feature = "mistral"), allow(dead_code))]
+pub(crate) fn record_model_used(app: &AppHandle, path: &Path) {
+    let mut state = load_state(app);
+    state
+        .last_used
+        .insert(registry_key(path), chrono::Utc::now().to_rfc3339());
+    if let Err(error) = save_state(app, &state) {
+        eprintln!("[Veil] model:registry:save:failed error={}", error);
+    }
+}
+
+pub(crate) fn model_entry_at(app: &AppHandle, path: &Path) -> Option<ModelEntry> {
+    model_entry(path, &load_state(app))
+}
+
+pub(crate) fn is_gguf(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("gguf"))
+        .unwrap_or(false)
+}
+
+/// The same file is reachable as `resources/veil.gguf`, through a symlink or
+/// as a relative `VEIL_MODEL_PATH`; the registry keys it by one path. A file
+/// that cannot be resolved keeps the path it was given.
+fn registry_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn model_entry(path: &Path, state: &RegistryState) -> Option<ModelEntry> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let key = registry_key(path);
+    Some(ModelEntry {
+        name: path.file_stem()?.to_string_lossy().to_string(),
+        path: path.display().to_string(),
+        size_bytes: metadata.len(),
+        last_used_at: state.last_used.get(&key).cloned(),
+        active: state.active_model_path.as_deref() == Some(key.as_path()),
+    })
+}
+
+fn registry_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(REGISTRY_FILE))
+        .map_err(|error| format!("Unable to resolve app data directory: {}", error))
+}
+
+fn load_state(app: &AppHandle) -> RegistryState {
+    registry_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app: &AppHandle, state: &RegistryState) -> Result<(), String> {
+    let path = registry_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|error| format!("Unable to create {}: {}", parent.display(), error))?;
+    }
+    let json = serde_json::to_string_pretty(state).map_err(|error| error.to_string())?;
+    std::fs::write(&path, json)
+        .map_err(|error| format!("Unable to write {}: {}", path.display(), error))
+}
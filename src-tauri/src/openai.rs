@@ -0,0 +1,606 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::backends::{
+    build_fallback_prompt, generation_ceiling_from_env, BackendError, Completion, CompletionUsage,
+    system_prompt, HoroscopeModelBackend,
+};
+use crate::commands::emit_status;
+use crate::generation::CancelToken;
+use crate::model_manager::ModelManager;
+use crate::schema::{dashboard_json_schema, reading_json_schema};
+use crate::types::{LoadStage, ModelStatus, ReadingRequest, SamplingParams};
+
+// Backend for a local server speaking the OpenAI chat-completions protocol
+// (llama.cpp server, Ollama, LM Studio, ...). Only compiled with the `openai`
+// cargo feature, and only used when `VEIL_OPENAI_BASE_URL` is set.
+// `examples/openai_stand_in.rs` is a minimal server to try it against.
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MODEL: &str = "veil";
+/// Role markers and turn separators a chat template adds around two messages.
+const CHAT_TEMPLATE_TOKENS: u32 = 16;
+
+#[derive(Clone, Debug)]
+pub(crate) struct OpenAiConfig {
+    /// Up to and including the API version, e.g. `http://127.0.0.1:8080/v1`.
+    /// Plain HTTP only: reqwest is built without TLS, as the servers this is
+    /// meant for run on the same machine.
+    pub(crate) base_url: String,
+    pub(crate) model: String,
+    pub(crate) api_key: Option<String>,
+    /// Send the JSON schemas as `response_format`. Servers without
+    /// structured-output support may reject it; disable with
+    /// `VEIL_OPENAI_CONSTRAINED=0`.
+    pub(crate) constrained: bool,
+    /// Requests in flight at once (`VEIL_OPENAI_MAX_CONCURRENT`, default 1).
+    /// Raise it to match the server's parallel slots.
+    pub(crate) max_concurrent: usize,
+    pub(crate) generation_ceiling: Duration,
+    /// The server's context window (`VEIL_OPENAI_CONTEXT_LENGTH`). llama.cpp
+    /// reports its own, so this is only needed for other servers.
+    pub(crate) context_length: Option<u32>,
+}
+
+impl OpenAiConfig {
+    pub(crate) fn from_env() -> Option<Self> {
+        let base_url = std::env::var("VEIL_OPENAI_BASE_URL").ok()?;
+        let base_url = base_url.trim().trim_end_matches('/').to_string();
+        if base_url.is_empty() {
+            return None;
+        }
+        Some(Self {
+            base_url,
+            model: std::env::var("VEIL_OPENAI_MODEL")
+                .ok()
+                .filter(|model| !model.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            api_key: std::env::var("VEIL_OPENAI_API_KEY")
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+            constrained: std::env::var("VEIL_OPENAI_CONSTRAINED").ok().as_deref() != Some("0"),
+            max_concurrent: std::env::var("VEIL_OPENAI_MAX_CONCURRENT")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(1),
+            generation_ceiling: generation_ceiling_from_env(),
+            context_length: std::env::var("VEIL_OPENAI_CONTEXT_LENGTH")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|tokens| *tokens > 0),
+        })
+    }
+}
+
+/// Connects in the background, reporting through `model:status` like a model
+/// load. The server counts as loaded once `GET /models` answers.
+pub(crate) fn connect(state: &ModelManager, app: &AppHandle, config: OpenAiConfig) {
+    state.set_status(ModelStatus::Loading {
+        progress: 0.0,
+        stage: LoadStage::Locating,
+        eta_ms: None,
+        bytes_done: 0,
+        bytes_total: 0,
+        indeterminate: false,
+    });
+    emit_status(app, state.get_status());
+
+    let state = state.clone();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match OpenAiBackend::connect(config).await {
+            Ok(backend) => {
+                let model_path = format!("{} ({})", backend.config.base_url, backend.config.model);
+                state.set_backend(Arc::new(backend));
+                state.set_status(ModelStatus::Loaded {
+                    model_path,
+                    model_size_mb: 0.0,
+                    model_size_bytes: 0,
+                    model_info: None,
+                    backend: "openai".to_string(),
+                });
+            }
+            Err(message) => state.set_status(ModelStatus::Error { message }),
+        }
+        emit_status(&app, state.get_status());
+    });
+}
+
+pub(crate) struct OpenAiBackend {
+    client: reqwest::Client,
+    config: OpenAiConfig,
+    context_length: Option<u32>,
+}
+
+impl OpenAiBackend {
+    async fn connect(config: OpenAiConfig) -> Result<Self, String> {
+        if config.base_url.starts_with("https://") {
+            return Err(format!(
+                "{} uses HTTPS, which this build cannot speak; use the server's http:// address.",
+                config.base_url
+            ));
+        }
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|error| format!("Unable to create HTTP client: {}", error))?;
+        let mut backend = Self {
+            client,
+            context_length: config.context_length,
+            config,
+        };
+
+        let url = format!("{}/models", backend.config.base_url);
+        let response = backend
+            .authorize(backend.client.get(&url))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await
+            .map_err(|error| {
+                format!(
+                    "No OpenAI-compatible server answered at {}: {}",
+                    backend.config.base_url, error
+                )
+            })?;
+        if !response.status().is_success() {
+            return Err(format!("{} returned {}.", url, response.status()));
+        }
+        // Servers that host one model (llama.cpp) ignore the name; others
+        // (Ollama) reject unknown ones, so flag a mismatch early in the log.
+        if let Ok(models) = response.json::<Value>().await {
+            let ids: Vec<&str> = models["data"]
+                .as_array()
+                .map(|data| data.iter().filter_map(|model| model["id"].as_str()).collect())
+                .unwrap_or_default();
+            if !ids.is_empty() && !ids.contains(&backend.config.model.as_str()) {
+                eprintln!(
+                    "[Veil] model:openai:model_not_listed model={} available={}",
+                    backend.config.model,
+                    ids.join(",")
+                );
+            }
+        }
+        if backend.context_length.is_none() {
+            backend.context_length = backend.probe_context_length().await;
+        }
+        Ok(backend)
+    }
+
+    /// llama.cpp's native endpoints (`/props`, `/tokenize`) live beside the
+    /// OpenAI-compatible API rather than under it.
+    fn server_root(&self) -> &str {
+        self.config.base_url.trim_end_matches("/v1")
+    }
+
+    /// Asks a llama.cpp server for the context size it was started with.
+    /// Other servers do not expose one.
+    async fn probe_context_length(&self) -> Option<u32> {
+        let url = format!("{}/props", self.server_root());
+        let response = self
+            .authorize(self.client.get(&url))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await
+            .ok()
+            .filter(|response| response.status().is_success())?;
+        let props = response.json::<Value>().await.ok()?;
+        let context_length = props["default_generation_settings"]["n_ctx"]
+            .as_u64()
+            .or_else(|| props["n_ctx"].as_u64())
+            .and_then(|tokens| u32::try_from(tokens).ok());
+        eprintln!("[Veil] model:openai:context_length tokens={:?}", context_length);
+        context_length
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.config.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    fn chat_body(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        schema: Value,
+        stream: bool,
+    ) -> Value {
+        let prompt = request
+            .prompt
+            .clone()
+            .unwrap_or_else(|| build_fallback_prompt(request));
+        let mut body = json!({
+            "model": self.config.model,
+            "messages": [
+                { "role": "system", "content": system_prompt(&request.persona) },
+                { "role": "user", "content": prompt },
+            ],
+            "stream": stream,
+            "temperature": sampling.temperature,
+            "top_p": sampling.top_p,
+            "max_tokens": sampling.max_tokens,
+            // Not in the OpenAI schema, but llama.cpp server and Ollama honour
+            // them and other servers ignore unknown fields.
+            "top_k": sampling.top_k,
+            "repeat_penalty": sampling.repeat_penalty,
+            // llama.cpp server: reuse the KV cache of the shared prompt prefix.
+            "cache_prompt": true,
+        });
+        if stream {
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        if let Some(seed) = sampling.seed {
+            body["seed"] = json!(seed);
+        }
+        if !sampling.stop.is_empty() {
+            body["stop"] = json!(sampling.stop);
+        }
+        if let Some(min_p) = sampling.min_p {
+            body["min_p"] = json!(min_p);
+        }
+        if let Some(penalty) = sampling.frequency_penalty {
+            body["frequency_penalty"] = json!(penalty);
+        }
+        if let Some(penalty) = sampling.presence_penalty {
+            body["presence_penalty"] = json!(penalty);
+        }
+        if let Some(logit_bias) = &sampling.logit_bias {
+            body["logit_bias"] = json!(logit_bias);
+        }
+        // llama.cpp server's names; servers without DRY ignore them.
+        if let Some(dry) = &sampling.dry {
+            body["dry_multiplier"] = json!(dry.multiplier);
+            if let Some(base) = dry.base {
+                body["dry_base"] = json!(base);
+            }
+            if let Some(length) = dry.allowed_length {
+                body["dry_allowed_length"] = json!(length);
+            }
+            if let Some(breakers) = &dry.sequence_breakers {
+                body["dry_sequence_breakers"] = json!(breakers);
+            }
+        }
+        if self.config.constrained {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": { "name": "veil", "strict": true, "schema": schema },
+            });
+        }
+        eprintln!(
+            "[Veil] model:invoke:start backend=openai stream={} constrained={}",
+            stream, self.config.constrained
+        );
+        body
+    }
+
+    /// Sends a chat request, giving up as soon as `cancel` fires. Dropping the
+    /// request closes the connection, which llama.cpp and Ollama treat as a
+    /// signal to stop decoding.
+    async fn send(
+        &self,
+        body: &Value,
+        cancel: &CancelToken,
+    ) -> Result<reqwest::Response, BackendError> {
+        let url = format!("{}/chat/completions", self.config.base_url);
+        let request = self.authorize(self.client.post(&url)).json(body).send();
+        let response = tokio::select! {
+            response = request => {
+                response.map_err(|error| BackendError::Failed(error.to_string()))?
+            }
+            _ = cancel.cancelled() => return Err(BackendError::Cancelled),
+        };
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(BackendError::Failed(format!(
+                "{} returned {}: {}",
+                url,
+                status,
+                detail.trim()
+            )));
+        }
+        Ok(response)
+    }
+
+    async fn complete(
+        &self,
+        body: Value,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        let started_at = std::time::Instant::now();
+        let response = self.send(&body, cancel).await?;
+        let completion: Value = tokio::select! {
+            completion = response.json() => {
+                completion.map_err(|error| BackendError::Failed(error.to_string()))?
+            }
+            _ = cancel.cancelled() => return Err(BackendError::Cancelled),
+        };
+        eprintln!(
+            "[Veil] model:invoke:complete backend=openai durationMs={}",
+            started_at.elapsed().as_millis()
+        );
+        let content = completion["choices"][0]["message"]["content"]
+            .as_str()
+            .filter(|content| !content.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| BackendError::Failed("Model returned empty content.".to_string()))?;
+        let mut usage = completion_usage(&completion);
+        // llama.cpp server reports its prompt processing time, which is the
+        // wait before the first token; other servers leave it unknown.
+        usage.time_to_first_token = completion["timings"]["prompt_ms"]
+            .as_f64()
+            .map(|ms| Duration::from_micros((ms * 1000.0) as u64));
+        Ok(Completion { content, usage })
+    }
+
+    /// Reads the server-sent event stream, forwarding each content delta.
+    /// Lines are split on raw bytes so a multi-byte character straddling two
+    /// network chunks is never decoded half-way.
+    async fn stream(
+        &self,
+        body: Value,
+        chunks: UnboundedSender<String>,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        let started_at = std::time::Instant::now();
+        let mut response = self.send(&body, cancel).await?;
+        let mut pending: Vec<u8> = Vec::new();
+        let mut content = String::new();
+        let mut usage = CompletionUsage::default();
+        'events: loop {
+            let bytes = tokio::select! {
+                bytes = response.chunk() => {
+                    bytes.map_err(|error| BackendError::Failed(error.to_string()))?
+                }
+                _ = cancel.cancelled() => return Err(BackendError::Cancelled),
+            };
+            let Some(bytes) = bytes else {
+                break;
+            };
+            pending.extend_from_slice(&bytes);
+            while let Some(newline) = pending.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:") else {
+                    continue;
+                };
+                let data = data.trim();
+                if data == "[DONE]" {
+                    break 'events;
+                }
+                let event: Value = serde_json::from_str(data).map_err(|error| {
+                    BackendError::Failed(format!("Bad stream event: {}", error))
+                })?;
+                if let Some(message) = event["error"]["message"].as_str() {
+                    return Err(BackendError::Failed(message.to_string()));
+                }
+                if let Some(delta) = event["choices"][0]["delta"]["content"].as_str() {
+                    if !delta.is_empty() {
+                        if usage.time_to_first_token.is_none() {
+                            usage.time_to_first_token = Some(started_at.elapsed());
+                        }
+                        content.push_str(delta);
+                        let _ = chunks.send(delta.to_string());
+                    }
+                }
+                if let Some(finish_reason) = event["choices"][0]["finish_reason"].as_str() {
+                    usage.finish_reason = Some(finish_reason.to_string());
+                }
+                // Sent last, and only because the body asks for `include_usage`.
+                if event["usage"].is_object() {
+                    usage = CompletionUsage {
+                        time_to_first_token: usage.time_to_first_token,
+                        finish_reason: usage.finish_reason.take(),
+                        ..completion_usage(&event)
+                    };
+                }
+            }
+        }
+        eprintln!(
+            "[Veil] model:stream:complete backend=openai durationMs={}",
+            started_at.elapsed().as_millis()
+        );
+        if content.is_empty() {
+            return Err(BackendError::Failed("Model returned empty content.".to_string()));
+        }
+        Ok(Completion { content, usage })
+    }
+}
+
+/// Token counts and finish reason from a completion, or from the final chunk
+/// of a stream.
+fn completion_usage(completion: &Value) -> CompletionUsage {
+    let tokens = |field: &str| completion["usage"][field].as_u64().map(|count| count as u32);
+    let rate = |field: &str| completion["timings"][field].as_f64().map(|rate| rate as f32);
+    CompletionUsage {
+        prompt_tokens: tokens("prompt_tokens"),
+        completion_tokens: tokens("completion_tokens"),
+        // llama.cpp server's own measurements; absent elsewhere.
+        tokens_per_second: rate("predicted_per_second"),
+        prompt_tokens_per_second: rate("prompt_per_second"),
+        prefix_cache_hit: completion["timings"]["cache_n"].as_u64().map(|cached| cached > 0),
+        finish_reason: completion["choices"][0]["finish_reason"]
+            .as_str()
+            .map(str::to_string),
+        ..CompletionUsage::default()
+    }
+}
+
+#[async_trait]
+impl HoroscopeModelBackend for OpenAiBackend {
+    async fn generate_json(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        let body = self.chat_body(request, sampling, reading_json_schema(), false);
+        self.complete(body, cancel).await
+    }
+
+    async fn stream_json(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        chunks: UnboundedSender<String>,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        let body = self.chat_body(request, sampling, reading_json_schema(), true);
+        self.stream(body, chunks, cancel).await
+    }
+
+    async fn generate_dashboard_json(
+        &self,
+        request: &ReadingRequest,
+        sampling: &SamplingParams,
+        cancel: &CancelToken,
+    ) -> Result<Completion, BackendError> {
+        let body = self.chat_body(request, sampling, dashboard_json_schema(), false);
+        self.complete(body, cancel).await
+    }
+
+    fn max_concurrent(&self) -> usize {
+        self.config.max_concurrent
+    }
+
+    fn generation_ceiling(&self) -> Duration {
+        self.config.generation_ceiling
+    }
+
+    fn context_length(&self) -> Option<u32> {
+        self.context_length
+    }
+
+    /// Uses llama.cpp's `/tokenize`. The chat template is not applied there,
+    /// so its markup is allowed for on top of the system and user text.
+    async fn count_prompt_tokens(&self, system_prompt: &str, prompt: &str) -> Option<u32> {
+        let url = format!("{}/tokenize", self.server_root());
+        let body = json!({
+            "content": format!("{}\n{}", system_prompt, prompt),
+            "add_special": true,
+        });
+        let response = self
+            .authorize(self.client.post(&url))
+            .json(&body)
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await
+            .ok()
+            .filter(|response| response.status().is_success())?;
+        let tokens = response.json::<Value>().await.ok()?["tokens"].as_array()?.len();
+        u32::try_from(tokens)
+            .ok()
+            .map(|tokens| tokens.saturating_add(CHAT_TEMPLATE_TOKENS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::types::{Persona, Profile};
+
+    #[allow(dead_code)]
+    mod stand_in {
+        include!("../examples/openai_stand_in.rs");
+    }
+
+    async fn connect_to_stand_in() -> OpenAiBackend {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("an ephemeral port");
+        let port = listener.local_addr().expect("a bound address").port();
+        std::thread::spawn(move || stand_in::serve(listener));
+        let config = OpenAiConfig {
+            base_url: format!("http://127.0.0.1:{}/v1", port),
+            model: DEFAULT_MODEL.to_string(),
+            api_key: None,
+            constrained: true,
+            max_concurrent: 1,
+            generation_ceiling: Duration::from_secs(30),
+            context_length: None,
+        };
+        OpenAiBackend::connect(config).await.expect("the stand-in answers")
+    }
+
+    fn request() -> ReadingRequest {
+        ReadingRequest {
+            profile: Profile {
+                name: "Ada".to_string(),
+                birthdate: "1990-04-02".to_string(),
+                mood: "Curious".to_string(),
+                personality: "The Builder".to_string(),
+            },
+            date: "2026-01-01".to_string(),
+            prompt: None,
+            sampling: SamplingParams::default(),
+            persona: Persona::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn generates_through_the_stand_in() {
+        let backend = connect_to_stand_in().await;
+        assert_eq!(backend.context_length(), Some(stand_in::CONTEXT_LENGTH));
+
+        let request = request();
+        let completion = backend
+            .generate_json(&request, &request.sampling, &CancelToken::new())
+            .await
+            .expect("a completion");
+        let reading: Value = serde_json::from_str(&completion.content).expect("JSON");
+        assert_eq!(reading["title"], "A Quiet Signal");
+        assert_eq!(completion.usage.finish_reason.as_deref(), Some("stop"));
+        assert!(completion.usage.completion_tokens.is_some());
+    }
+
+    #[tokio::test]
+    async fn streams_through_the_stand_in() {
+        let backend = connect_to_stand_in().await;
+        let request = request();
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let completion = backend
+            .stream_json(&request, &request.sampling, sender, &CancelToken::new())
+            .await
+            .expect("a completion");
+
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = receiver.try_recv() {
+            chunks.push(chunk);
+        }
+        // One delta per word, each intact although its event arrived in two
+        // pieces, some split inside the em dash.
+        assert!(chunks.len() > 10);
+        assert_eq!(chunks.concat(), completion.content);
+        let reading: Value = serde_json::from_str(&completion.content).expect("JSON");
+        assert!(reading["message"].as_str().unwrap_or_default().contains(" — "));
+        // Usage arrives in the last event before `[DONE]` and keeps the
+        // finish reason from the one before it.
+        assert_eq!(completion.usage.finish_reason.as_deref(), Some("stop"));
+        assert!(completion.usage.completion_tokens.is_some());
+        assert!(completion.usage.time_to_first_token.is_some());
+    }
+
+    #[tokio::test]
+    async fn refuses_https() {
+        let config = OpenAiConfig {
+            base_url: "https://127.0.0.1:1/v1".to_string(),
+            model: DEFAULT_MODEL.to_string(),
+            api_key: None,
+            constrained: true,
+            max_concurrent: 1,
+            generation_ceiling: Duration::from_secs(30),
+            context_length: None,
+        };
+        let Err(error) = OpenAiBackend::connect(config).await else {
+            panic!("an https:// address should be refused");
+        };
+        assert!(error.contains("HTTPS"));
+    }
+}
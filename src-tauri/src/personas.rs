@@ -0,0 +1,385 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+
+use crate::types::{Persona, SamplingParams, StubVocabulary};
+
+// Voices a reading can be written in. Each persona carries the voice part of
+// the system prompt (the JSON rules are appended by the backends), the
+// sampling used when a request brings none, and the word lists the offline
+// generator draws from. User-defined personas are JSON files in
+// `<app config dir>/personas`, one per file.
+
+const PERSONA_DIR: &str = "personas";
+pub(crate) const DEFAULT_PERSONA: &str = "veil";
+/// The reading stub shows three themes.
+const MIN_THEMES: usize = 3;
+
+impl Default for Persona {
+    fn default() -> Self {
+        veil()
+    }
+}
+
+impl Default for StubVocabulary {
+    fn default() -> Self {
+        StubVocabulary {
+            titles: strings(&[
+                "The hush before a bright idea",
+                "Soft focus, clear intention",
+                "A horizon you can trust",
+                "The spark beneath stillness",
+                "A graceful return to center",
+            ]),
+            openings: strings(&[
+                "Today opens with a {mood} current that invites gentler choices.",
+                "The day moves at a {mood} pace, offering room to breathe.",
+                "You may notice a {mood} undertone guiding your timing.",
+            ]),
+            middles: strings(&[
+                "As {sign}, your {personality} nature notices patterns others miss, so trust what quietly repeats.",
+                "Your {personality} instincts highlight what is worth protecting and what can soften.",
+                "The {personality} in you is ready to translate intuition into a simple next step.",
+            ]),
+            closers: strings(&[
+                "Let small rituals ground you, and remember that clarity arrives in layers, not lightning bolts.",
+                "If you pause before responding, the right phrasing will rise on its own.",
+                "Choose one gentle action that honors your energy, and let that be enough.",
+            ]),
+            themes: strings(&[
+                "Quiet confidence",
+                "Meaningful timing",
+                "Boundaries with kindness",
+                "Creative listening",
+                "Soft courage",
+                "Steady focus",
+            ]),
+            affirmations: strings(&[
+                "I meet today with grounded curiosity.",
+                "I can move gently and still be powerful.",
+                "My inner compass grows clearer with every breath.",
+                "I honor what I feel and choose what I need.",
+            ]),
+            lucky_colors: strings(&[
+                "Moonlit Indigo",
+                "Starlight Silver",
+                "Luminous Lavender",
+                "Sea-glass Teal",
+                "Amber Mist",
+            ]),
+        }
+    }
+}
+
+fn veil() -> Persona {
+    Persona {
+        id: DEFAULT_PERSONA.to_string(),
+        name: "Veil".to_string(),
+        description: "Warm and gently mystical; the original voice.".to_string(),
+        system_prompt: "You are Veil, a warm feminine astrologer with a loving aura. You are an expert who writes premium, modern astrology.".to_string(),
+        sampling: SamplingParams::default(),
+        stub: StubVocabulary::default(),
+        builtin: true,
+    }
+}
+
+fn builtin_personas() -> Vec<Persona> {
+    let defaults = SamplingParams::default();
+    vec![
+        veil(),
+        Persona {
+            id: "grounded".to_string(),
+            name: "Grounded".to_string(),
+            description: "Stoic and practical; advice over atmosphere.".to_string(),
+            system_prompt: "You are a grounded, stoic astrologer. You speak plainly, favour practical advice over atmosphere, and treat the stars as a prompt for reflection rather than a prophecy.".to_string(),
+            sampling: SamplingParams {
+                temperature: 0.3,
+                top_p: 0.85,
+                ..defaults.clone()
+            },
+            stub: StubVocabulary {
+                titles: strings(&[
+                    "Do the next right thing",
+                    "Steady hands, clear head",
+                    "What is yours to control",
+                    "A day for fundamentals",
+                ]),
+                openings: strings(&[
+                    "Today runs at a {mood} pace; plan around it rather than against it.",
+                    "Expect a {mood} day and set your expectations to match.",
+                    "A {mood} mood is information, not a verdict; work with it.",
+                ]),
+                middles: strings(&[
+                    "Your {personality} side is useful today: put it to a concrete task.",
+                    "Lean on your {personality} habits and keep the list short.",
+                    "The {personality} in you already knows the next practical step; take it.",
+                ]),
+                closers: strings(&[
+                    "Attend to what is in front of you and let the rest wait.",
+                    "Finish one thing properly before starting another.",
+                    "Say less, do the work, and judge the day by its effort.",
+                ]),
+                themes: strings(&[
+                    "Discipline",
+                    "Patience",
+                    "Plain speech",
+                    "Follow-through",
+                    "Acceptance",
+                ]),
+                affirmations: strings(&[
+                    "I act on what I can change.",
+                    "I do my part and release the outcome.",
+                    "Steady effort is enough today.",
+                ]),
+                lucky_colors: strings(&["Slate", "Oak Brown", "Stone Grey", "Forest Green"]),
+            },
+            builtin: true,
+        },
+        Persona {
+            id: "playful".to_string(),
+            name: "Playful".to_string(),
+            description: "Light, witty and upbeat.".to_string(),
+            system_prompt: "You are a playful, witty astrologer. You write with a light touch and a wink, keep the mood upbeat, and never let a joke undercut a kind word.".to_string(),
+            sampling: SamplingParams {
+                temperature: 0.8,
+                top_p: 0.95,
+                presence_penalty: Some(0.2),
+                ..defaults.clone()
+            },
+            stub: StubVocabulary {
+                titles: strings(&[
+                    "The stars brought snacks",
+                    "Main character energy, lightly applied",
+                    "Plot twist: it goes fine",
+                    "Cosmic permission to have fun",
+                ]),
+                openings: strings(&[
+                    "Today shows up in a {mood} mood, and it brought confetti.",
+                    "The vibe is {mood}, with a fair chance of pleasant surprises.",
+                    "Your day rolls in feeling {mood} and ready for a little mischief.",
+                ]),
+                middles: strings(&[
+                    "Your {personality} streak is today's secret weapon; use it generously.",
+                    "Let the {personality} in you pick the playlist and the plan.",
+                    "Your {personality} charm opens doors, so knock on a few.",
+                ]),
+                closers: strings(&[
+                    "Say yes to the small adventure; the universe loves a good side quest.",
+                    "Laugh first and decide second; your timing improves with a grin.",
+                    "Treat yourself to something silly and call it self-care.",
+                ]),
+                themes: strings(&[
+                    "Spontaneity",
+                    "Good company",
+                    "Bold little moves",
+                    "Curiosity",
+                    "Lightness",
+                ]),
+                affirmations: strings(&[
+                    "I let joy lead the way today.",
+                    "I am allowed to enjoy this.",
+                    "My good mood is contagious.",
+                ]),
+                lucky_colors: strings(&[
+                    "Bubblegum Pink",
+                    "Citrus Yellow",
+                    "Electric Teal",
+                    "Tangerine",
+                ]),
+            },
+            builtin: true,
+        },
+        Persona {
+            id: "poetic".to_string(),
+            name: "Poetic".to_string(),
+            description: "Lyrical and image-rich.".to_string(),
+            system_prompt: "You are a poetic astrologer. You write in lyrical, image-rich language, with rhythm and metaphor, while keeping every sentence clear enough to act on.".to_string(),
+            sampling: SamplingParams {
+                temperature: 0.75,
+                top_p: 0.95,
+                min_p: Some(0.05),
+                ..defaults.clone()
+            },
+            stub: StubVocabulary {
+                titles: strings(&[
+                    "Tidewater and lantern light",
+                    "The moon keeps your secret",
+                    "A river learning its name",
+                    "Embers under snow",
+                ]),
+                openings: strings(&[
+                    "The day arrives {mood}, like light through a half-drawn curtain.",
+                    "A {mood} tide moves beneath the hours, slow and sure.",
+                    "Morning carries a {mood} hush, the kind that holds a question.",
+                ]),
+                middles: strings(&[
+                    "Your {personality} heart reads the small signs the way sailors read stars.",
+                    "The {personality} in you gathers the day's loose threads into something whole.",
+                    "Let your {personality} nature be the lantern, not the map.",
+                ]),
+                closers: strings(&[
+                    "Let the day unfold like a letter you were always meant to receive.",
+                    "Move as water does: patient, certain, finding every opening.",
+                    "Carry a small light, and the path will rise to meet it.",
+                ]),
+                themes: strings(&[
+                    "Tides",
+                    "Quiet fire",
+                    "Unfolding",
+                    "Moonlit thought",
+                    "Return",
+                ]),
+                affirmations: strings(&[
+                    "I am the lantern and the road.",
+                    "I bloom in my own season.",
+                    "My heart knows the way home.",
+                ]),
+                lucky_colors: strings(&["Dusk Violet", "Pearl", "Midnight Blue", "Rose Gold"]),
+            },
+            builtin: true,
+        },
+        Persona {
+            id: "minimalist".to_string(),
+            name: "Minimalist".to_string(),
+            description: "Short, spare sentences; only what matters.".to_string(),
+            system_prompt: "You are a minimalist astrologer. You use short, spare sentences, cut every word that does not earn its place, and never decorate.".to_string(),
+            sampling: SamplingParams {
+                temperature: 0.35,
+                ..defaults
+            },
+            stub: StubVocabulary {
+                titles: strings(&["Less, but better", "One clear step", "Quiet focus", "Enough"]),
+                openings: strings(&[
+                    "A {mood} day.",
+                    "Mood: {mood}. Work with it.",
+                    "Today is {mood}. Keep pace.",
+                ]),
+                middles: strings(&[
+                    "Use your {personality} side.",
+                    "Trust the {personality} in you.",
+                    "Your {personality} instinct knows the step.",
+                ]),
+                closers: strings(&[
+                    "Do one thing well.",
+                    "Rest is part of the work.",
+                    "Keep it simple.",
+                ]),
+                themes: strings(&["Focus", "Space", "Clarity", "Calm", "Essentials"]),
+                affirmations: strings(&[
+                    "I have enough.",
+                    "I choose what matters.",
+                    "I keep it simple.",
+                ]),
+                lucky_colors: strings(&["White", "Black", "Sand", "Grey"]),
+            },
+            builtin: true,
+        },
+    ]
+}
+
+/// Built-in personas followed by the user's, by ID.
+pub(crate) fn list_personas(app: &AppHandle) -> Vec<Persona> {
+    let mut personas = builtin_personas();
+    personas.extend(load_user_personas(app));
+    personas
+}
+
+/// The persona with `id`, or the default when none is given.
+pub(crate) fn resolve(app: &AppHandle, id: Option<&str>) -> Result<Persona, String> {
+    let id = id.map(str::trim).filter(|id| !id.is_empty()).unwrap_or(DEFAULT_PERSONA);
+    list_personas(app)
+        .into_iter()
+        .find(|persona| persona.id == id)
+        .ok_or_else(|| format!("No persona named {}.", id))
+}
+
+fn persona_directory(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(PERSONA_DIR))
+}
+
+/// Reads `*.json` from the persona directory, skipping (and logging) files
+/// that do not parse, reuse a built-in ID or carry out-of-range sampling.
+fn load_user_personas(app: &AppHandle) -> Vec<Persona> {
+    let Some(directory) = persona_directory(app) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&directory) else {
+        return Vec::new();
+    };
+    let builtin_ids: Vec<String> = builtin_personas()
+        .into_iter()
+        .map(|persona| persona.id)
+        .collect();
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    paths.sort();
+
+    let mut personas: Vec<Persona> = Vec::new();
+    for path in paths {
+        let persona = std::fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<Persona>(&json).map_err(|error| error.to_string())
+            })
+            .and_then(|persona| check_user_persona(persona, &builtin_ids, &personas));
+        match persona {
+            Ok(persona) => personas.push(persona),
+            Err(error) => eprintln!(
+                "[Veil] persona:load:skipped path={} error={}",
+                path.display(),
+                error
+            ),
+        }
+    }
+    personas
+}
+
+fn check_user_persona(
+    mut persona: Persona,
+    builtin_ids: &[String],
+    loaded: &[Persona],
+) -> Result<Persona, String> {
+    if persona.id.trim().is_empty() {
+        return Err("Persona id is empty.".to_string());
+    }
+    if builtin_ids.contains(&persona.id) || loaded.iter().any(|other| other.id == persona.id) {
+        return Err(format!("Persona id {} is already taken.", persona.id));
+    }
+    persona.sampling.validate().map_err(|errors| {
+        errors
+            .iter()
+            .map(|error| format!("sampling.{}: {}", error.field, error.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    })?;
+    // An empty word list would leave the offline generator nothing to pick.
+    let defaults = StubVocabulary::default();
+    let stub = &mut persona.stub;
+    for (list, default) in [
+        (&mut stub.titles, defaults.titles),
+        (&mut stub.openings, defaults.openings),
+        (&mut stub.middles, defaults.middles),
+        (&mut stub.closers, defaults.closers),
+        (&mut stub.affirmations, defaults.affirmations),
+        (&mut stub.lucky_colors, defaults.lucky_colors),
+    ] {
+        if list.is_empty() {
+            *list = default;
+        }
+    }
+    if stub.themes.len() < MIN_THEMES {
+        stub.themes = defaults.themes;
+    }
+    persona.builtin = false;
+    Ok(persona)
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
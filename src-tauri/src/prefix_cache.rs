@@ -0,0 +1,90 @@
+use std::sync::Mutex;
+
+// mistral.rs keeps a prefix cache but does not report when it used it. A
+// reused prefix skips most prompt processing, so a hit shows up as prompt
+// throughput far above what the model manages on a cold prompt. This keeps
+// the cold throughput as a baseline and measures each call against it.
+
+/// Prompt throughput this many times the cold baseline counts as a hit.
+const HIT_SPEEDUP: f32 = 3.0;
+/// Weight of each cold call in the baseline's moving average.
+const BASELINE_WEIGHT: f32 = 0.25;
+/// Shorter prompts finish too quickly for their throughput to mean much.
+const MIN_PROMPT_TOKENS: u32 = 64;
+
+#[derive(Default)]
+pub(crate) struct PrefixCacheProbe {
+    state: Mutex<ProbeState>,
+}
+
+#[derive(Default)]
+struct ProbeState {
+    calls: u32,
+    /// Cold prompt throughput, once a call has set it.
+    baseline: Option<f32>,
+}
+
+impl PrefixCacheProbe {
+    /// Whether a call that processed `prompt_tokens` at `tokens_per_second`
+    /// reused a cached prefix; `None` when the measurement cannot tell.
+    ///
+    /// The first call after a load always misses (the cache is empty) but
+    /// also pays for warm-up, so it does not set the baseline; the second
+    /// does. A baseline seeded by a hit is too high, and cold calls pull it
+    /// back down; only cold calls update it.
+    pub(crate) fn observe(&self, prompt_tokens: u32, tokens_per_second: f32) -> Option<bool> {
+        let mut state = self.state.lock().ok()?;
+        state.calls += 1;
+        if state.calls == 1 {
+            return Some(false);
+        }
+        if prompt_tokens < MIN_PROMPT_TOKENS
+            || !tokens_per_second.is_finite()
+            || tokens_per_second <= 0.0
+        {
+            return None;
+        }
+        let Some(cold) = state.baseline else {
+            state.baseline = Some(tokens_per_second);
+            return None;
+        };
+        if tokens_per_second >= cold * HIT_SPEEDUP {
+            return Some(true);
+        }
+        state.baseline = Some(cold + (tokens_per_second - cold) * BASELINE_WEIGHT);
+        Some(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_first_call_as_a_miss_and_seeds_on_the_second() {
+        let probe = PrefixCacheProbe::default();
+        assert_eq!(probe.observe(1500, 80.0), Some(false));
+        assert_eq!(probe.observe(1500, 400.0), None);
+        assert_eq!(probe.observe(1500, 420.0), Some(false));
+        assert_eq!(probe.observe(1500, 3000.0), Some(true));
+    }
+
+    #[test]
+    fn recovers_from_a_baseline_seeded_by_a_hit() {
+        let probe = PrefixCacheProbe::default();
+        probe.observe(1500, 80.0);
+        probe.observe(1500, 3000.0);
+        for _ in 0..12 {
+            assert_eq!(probe.observe(1500, 400.0), Some(false));
+        }
+        assert_eq!(probe.observe(1500, 3000.0), Some(true));
+    }
+
+    #[test]
+    fn says_nothing_about_short_prompts() {
+        let probe = PrefixCacheProbe::default();
+        probe.observe(1500, 80.0);
+        assert_eq!(probe.observe(12, 5000.0), None);
+        assert_eq!(probe.observe(1500, 0.0), None);
+    }
+}
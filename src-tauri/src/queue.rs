@@ -0,0 +1,276 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::backends::BackendError;
+use crate::generation::CancelToken;
+use crate::types::{GenerationPriority, QueueEvent};
+
+const DEFAULT_CAPACITY: usize = 16;
+
+/// Admits generations to the backend a few at a time. Requests beyond the
+/// backend's concurrency limit wait in priority order (interactive before
+/// background, then first come first served), and each waiter is told its
+/// position through `model:queue` events whenever it changes.
+#[derive(Clone)]
+pub(crate) struct InferenceQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+struct QueueState {
+    running: usize,
+    limit: usize,
+    capacity: usize,
+    next_seq: u64,
+    waiting: Vec<Waiter>,
+}
+
+struct Waiter {
+    request_id: String,
+    priority: GenerationPriority,
+    seq: u64,
+    /// Last position sent, so unchanged positions are not re-announced.
+    position: usize,
+    updates: UnboundedSender<WaiterUpdate>,
+}
+
+enum WaiterUpdate {
+    Position(usize),
+    Start,
+    /// Pushed out of a full queue by an interactive request.
+    Evicted,
+}
+
+impl Default for InferenceQueue {
+    fn default() -> Self {
+        let capacity = std::env::var("VEIL_QUEUE_CAPACITY")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        Self {
+            state: Arc::new(Mutex::new(QueueState {
+                running: 0,
+                limit: 1,
+                capacity,
+                next_seq: 0,
+                waiting: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl InferenceQueue {
+    /// Applies a new backend's concurrency limit. Raising it starts waiters
+    /// right away; lowering it lets running generations finish.
+    pub(crate) fn set_limit(&self, limit: usize) {
+        if let Ok(mut state) = self.state.lock() {
+            state.limit = limit.max(1);
+            state.dispatch();
+        }
+    }
+
+    /// Waits for a generation slot. The returned permit holds the slot until
+    /// it is dropped. Cancelling while queued gives up the place in line.
+    pub(crate) async fn acquire(
+        &self,
+        app: &AppHandle,
+        request_id: &str,
+        priority: GenerationPriority,
+        cancel: &CancelToken,
+    ) -> Result<QueuePermit, BackendError> {
+        let queued_at = Instant::now();
+        let (sender, mut updates) = unbounded_channel();
+        let seq = {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|_| BackendError::Failed("Inference queue is unavailable.".to_string()))?;
+            if state.waiting.is_empty() && state.running < state.limit {
+                state.running += 1;
+                drop(state);
+                emit_queue_event(app, started(request_id, queued_at));
+                return Ok(QueuePermit { queue: self.clone() });
+            }
+            if state.waiting.len() >= state.capacity && !state.evict_background(priority) {
+                eprintln!(
+                    "[Veil] model:queue:full requestId={} waiting={}",
+                    request_id,
+                    state.waiting.len()
+                );
+                return Err(BackendError::Failed(
+                    "Too many readings are already queued; try again shortly.".to_string(),
+                ));
+            }
+            state.enqueue(request_id, priority, sender)
+        };
+
+        loop {
+            let update = tokio::select! {
+                update = updates.recv() => update,
+                _ = cancel.cancelled() => {
+                    self.leave(seq);
+                    // The slot may have been handed over just before the
+                    // cancel won the race; give it back.
+                    while let Ok(update) = updates.try_recv() {
+                        if matches!(update, WaiterUpdate::Start) {
+                            self.release();
+                        }
+                    }
+                    return Err(BackendError::Cancelled);
+                }
+            };
+            match update {
+                Some(WaiterUpdate::Position(position)) => emit_queue_event(
+                    app,
+                    QueueEvent::Queued {
+                        request_id: request_id.to_string(),
+                        position,
+                        priority,
+                    },
+                ),
+                Some(WaiterUpdate::Start) => {
+                    emit_queue_event(app, started(request_id, queued_at));
+                    return Ok(QueuePermit { queue: self.clone() });
+                }
+                Some(WaiterUpdate::Evicted) | None => {
+                    return Err(BackendError::Failed(
+                        "A more urgent reading took this request's place in the queue.".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Removes one waiter by its sequence number. A request ID can be reused
+    /// while the generation it cancelled is still leaving the queue, so the
+    /// ID alone would also remove the new waiter.
+    fn leave(&self, seq: u64) {
+        if let Ok(mut state) = self.state.lock() {
+            let before = state.waiting.len();
+            state.waiting.retain(|waiter| waiter.seq != seq);
+            if state.waiting.len() != before {
+                state.announce_positions();
+            }
+        }
+    }
+
+    fn release(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.running = state.running.saturating_sub(1);
+            state.dispatch();
+        }
+    }
+}
+
+impl QueueState {
+    /// Adds a waiter in priority order and returns its sequence number.
+    fn enqueue(
+        &mut self,
+        request_id: &str,
+        priority: GenerationPriority,
+        updates: UnboundedSender<WaiterUpdate>,
+    ) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.waiting.push(Waiter {
+            request_id: request_id.to_string(),
+            priority,
+            seq,
+            position: 0,
+            updates,
+        });
+        self.waiting.sort_by_key(|waiter| (waiter.priority, waiter.seq));
+        self.announce_positions();
+        seq
+    }
+
+    /// Starts waiters while slots are free.
+    fn dispatch(&mut self) {
+        let mut moved = false;
+        while self.running < self.limit && !self.waiting.is_empty() {
+            let waiter = self.waiting.remove(0);
+            moved = true;
+            // A waiter whose command already returned has dropped its receiver.
+            if waiter.updates.send(WaiterUpdate::Start).is_ok() {
+                self.running += 1;
+            }
+        }
+        if moved {
+            self.announce_positions();
+        }
+    }
+
+    /// Makes room for a request of `priority` by dropping the newest waiter
+    /// that is strictly less urgent. Returns whether one was dropped.
+    fn evict_background(&mut self, priority: GenerationPriority) -> bool {
+        let Some(index) = self.waiting.iter().rposition(|waiter| waiter.priority > priority) else {
+            return false;
+        };
+        let waiter = self.waiting.remove(index);
+        eprintln!("[Veil] model:queue:evicted requestId={}", waiter.request_id);
+        let _ = waiter.updates.send(WaiterUpdate::Evicted);
+        true
+    }
+
+    fn announce_positions(&mut self) {
+        for (index, waiter) in self.waiting.iter_mut().enumerate() {
+            if waiter.position != index + 1 {
+                waiter.position = index + 1;
+                let _ = waiter.updates.send(WaiterUpdate::Position(index + 1));
+            }
+        }
+    }
+}
+
+/// A running generation's slot; dropping it admits the next waiter.
+pub(crate) struct QueuePermit {
+    queue: InferenceQueue,
+}
+
+impl Drop for QueuePermit {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+fn started(request_id: &str, queued_at: Instant) -> QueueEvent {
+    QueueEvent::Started {
+        request_id: request_id.to_string(),
+        waited_ms: queued_at.elapsed().as_millis() as u64,
+    }
+}
+
+fn emit_queue_event(app: &AppHandle, event: QueueEvent) {
+    let _ = app.emit("model:queue", event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaving_keeps_a_new_waiter_under_a_reused_id() {
+        let queue = InferenceQueue::default();
+        let (old_sender, _old_updates) = unbounded_channel();
+        let (new_sender, mut new_updates) = unbounded_channel();
+        let old_seq = {
+            let mut state = queue.state.lock().unwrap();
+            state.running = state.limit;
+            let old_seq = state.enqueue("dashboard-1", GenerationPriority::Interactive, old_sender);
+            state.enqueue("dashboard-1", GenerationPriority::Interactive, new_sender);
+            old_seq
+        };
+
+        queue.leave(old_seq);
+        queue.release();
+
+        let mut started = false;
+        while let Ok(update) = new_updates.try_recv() {
+            started |= matches!(update, WaiterUpdate::Start);
+        }
+        assert!(started, "the requeued waiter should get the freed slot");
+        assert!(queue.state.lock().unwrap().waiting.is_empty());
+    }
+}
@@ -0,0 +1,297 @@
+// Best-effort clean-up of model output that is almost, but not quite, JSON.
+// Every step works on the raw text (no parsing) and only fixes mistakes small
+// models commonly make: markdown fences, chatter around the object, trailing
+// commas and output cut off by `max_tokens`.
+
+/// Returns a repaired copy of `raw`, or `None` when it contains no object.
+pub(crate) fn repair_json(raw: &str) -> Option<String> {
+    let unfenced = strip_markdown_fences(raw);
+    let object = extract_first_object(&unfenced)?;
+    let without_trailing_commas = remove_trailing_commas(object);
+    Some(close_truncated(&without_trailing_commas))
+}
+
+/// Appended to the original prompt when the first attempt could not be parsed.
+pub(crate) fn build_reprompt(original_prompt: &str, parse_error: &str) -> String {
+    format!(
+        "{}\n\nYOUR PREVIOUS ANSWER WAS REJECTED:\n{}\nReturn the complete JSON object again. Strict JSON only: no markdown, no commentary, no trailing commas.",
+        original_prompt, parse_error
+    )
+}
+
+/// Removes fence markers (and an opening fence's language tag) but keeps the
+/// rest of their line, which may hold the object itself: ```` ```json {..}``` ````.
+fn strip_markdown_fences(raw: &str) -> String {
+    raw.lines()
+        .map(|line| {
+            let line = match line.trim_start().strip_prefix("```") {
+                Some(rest) => rest.trim_start_matches(|ch: char| ch.is_ascii_alphanumeric()),
+                None => line,
+            };
+            line.trim_end().strip_suffix("```").unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Slices from the first `{` to its matching `}`. If the text ends before the
+/// object is closed, the remainder is returned so it can be closed later.
+fn extract_first_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, ch) in text[start..].char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Some(&text[start..start + offset + ch.len_utf8()]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(&text[start..])
+}
+
+fn remove_trailing_commas(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, &ch) in chars.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+            output.push(ch);
+            continue;
+        }
+        if ch == '"' {
+            in_string = true;
+        }
+        if ch == ',' {
+            let next = chars[index + 1..].iter().find(|next| !next.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        output.push(ch);
+    }
+    output
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Key,
+    Colon,
+    Value,
+    Separator,
+}
+
+struct Frame {
+    closer: char,
+    expect: Expect,
+}
+
+/// Closes an unterminated string, fills a dangling key or colon with `null`,
+/// completes a cut-off `true`/`false`/`null`, and appends missing closers.
+fn close_truncated(text: &str) -> String {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut in_string = false;
+    let mut string_is_key = false;
+    let mut escaped = false;
+
+    for ch in text.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+                if let Some(frame) = stack.last_mut() {
+                    frame.expect = if string_is_key { Expect::Colon } else { Expect::Separator };
+                }
+            }
+            continue;
+        }
+        match ch {
+            '"' => {
+                in_string = true;
+                string_is_key = stack
+                    .last()
+                    .map(|frame| frame.closer == '}' && frame.expect == Expect::Key)
+                    .unwrap_or(false);
+            }
+            '{' => stack.push(Frame { closer: '}', expect: Expect::Key }),
+            '[' => stack.push(Frame { closer: ']', expect: Expect::Value }),
+            '}' | ']' => {
+                stack.pop();
+                if let Some(frame) = stack.last_mut() {
+                    frame.expect = Expect::Separator;
+                }
+            }
+            ':' => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.expect = Expect::Value;
+                }
+            }
+            ',' => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.expect = if frame.closer == '}' { Expect::Key } else { Expect::Value };
+                }
+            }
+            ch if !ch.is_whitespace() => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.expect = Expect::Separator;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if stack.is_empty() && !in_string {
+        return text.to_string();
+    }
+
+    let mut output = text.to_string();
+    if in_string {
+        if escaped {
+            output.pop();
+        }
+        output.push('"');
+        if let Some(frame) = stack.last_mut() {
+            frame.expect = if string_is_key { Expect::Colon } else { Expect::Separator };
+        }
+    }
+    let trimmed_len = output.trim_end().len();
+    output.truncate(trimmed_len);
+    complete_partial_literal(&mut output);
+    if output.ends_with(',') {
+        output.pop();
+    }
+
+    if output.ends_with(':') {
+        output.push_str(" null");
+    } else if matches!(stack.last(), Some(frame) if frame.expect == Expect::Colon) {
+        output.push_str(": null");
+    }
+    for frame in stack.iter().rev() {
+        output.push(frame.closer);
+    }
+    output
+}
+
+fn complete_partial_literal(output: &mut String) {
+    let token_start = output
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| matches!(ch, ':' | ',' | '[' | '{') || ch.is_whitespace())
+        .map(|(index, ch)| index + ch.len_utf8())
+        .unwrap_or(0);
+    let token = output[token_start..].to_string();
+    if token.is_empty() || token.starts_with('"') {
+        return;
+    }
+    for literal in ["true", "false", "null"] {
+        if literal.starts_with(token.as_str()) {
+            output.push_str(&literal[token.len()..]);
+            return;
+        }
+    }
+    // A number cut off after its sign, decimal point or exponent marker.
+    while output.ends_with(['-', '+', '.', 'e', 'E']) {
+        output.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repaired(raw: &str) -> serde_json::Value {
+        let json = repair_json(raw).expect("an object");
+        serde_json::from_str(&json).unwrap_or_else(|error| panic!("{json}: {error}"))
+    }
+
+    #[test]
+    fn strips_fences_on_their_own_lines() {
+        let raw = "Here you go:\n```json\n{\"a\": 1}\n```\nEnjoy!";
+        assert_eq!(repaired(raw), serde_json::json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn keeps_an_object_on_the_fence_line() {
+        assert_eq!(repaired("```json {\"a\":1}```"), serde_json::json!({ "a": 1 }));
+        assert_eq!(repaired("```{\"a\":1}\n```"), serde_json::json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn removes_trailing_commas() {
+        let raw = r#"{"a": [1, 2,], "b": {"c": "x",},}"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": [1, 2], "b": { "c": "x" } }));
+    }
+
+    #[test]
+    fn keeps_commas_inside_strings() {
+        let raw = r#"{"a": "one, }two,]"}"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": "one, }two,]" }));
+    }
+
+    #[test]
+    fn closes_output_cut_off_inside_a_string() {
+        let raw = r#"{"a": {"b": ["soft", "gent"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": { "b": ["soft", "gent"] } }));
+    }
+
+    #[test]
+    fn closes_output_cut_off_inside_a_key() {
+        assert_eq!(repaired(r#"{"a": 1, "ke"#), serde_json::json!({ "a": 1, "ke": null }));
+        assert_eq!(repaired(r#"{"a": 1, "key":"#), serde_json::json!({ "a": 1, "key": null }));
+    }
+
+    #[test]
+    fn completes_cut_off_literals() {
+        let raw = r#"{"a": tr"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": true }));
+        let raw = r#"{"a": [fals"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": [false] }));
+        let raw = r#"{"a": 1, "b": n"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": 1, "b": null }));
+        let raw = r#"{"a": 1."#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn respects_escaped_quotes() {
+        let raw = r#"{"a": "say \"hi\" {", "b": "x"}"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": "say \"hi\" {", "b": "x" }));
+        let raw = r#"{"a": "say \"hi"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": "say \"hi" }));
+        let raw = r#"{"a": "cut \"#;
+        assert_eq!(repaired(raw), serde_json::json!({ "a": "cut " }));
+    }
+
+    #[test]
+    fn finds_no_object_in_prose() {
+        assert_eq!(repair_json("I cannot help with that."), None);
+    }
+}
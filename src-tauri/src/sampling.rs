@@ -0,0 +1,176 @@
+use std::ops::RangeInclusive;
+
+use crate::types::{FieldError, SamplingParams};
+
+// Range checks for sampling parameters that arrive from the frontend. Serde
+// already rejects wrong types; these catch values the engines would accept
+// but misbehave on (a `topP` of 5, a negative temperature, `maxTokens` of 0).
+
+const TEMPERATURE: RangeInclusive<f32> = 0.0..=2.0;
+const TOP_P: RangeInclusive<f32> = 0.01..=1.0;
+const TOP_K: RangeInclusive<u32> = 1..=1000;
+const REPEAT_PENALTY: RangeInclusive<f32> = 1.0..=2.0;
+const MAX_TOKENS: RangeInclusive<u32> = 1..=32768;
+const MIN_P: RangeInclusive<f32> = 0.0..=1.0;
+/// The OpenAI API's range for frequency and presence penalties.
+const PENALTY: RangeInclusive<f32> = -2.0..=2.0;
+const DRY_MULTIPLIER: RangeInclusive<f32> = 0.0..=5.0;
+const DRY_BASE: RangeInclusive<f32> = 1.0..=4.0;
+const DRY_ALLOWED_LENGTH: RangeInclusive<u32> = 1..=64;
+const LOGIT_BIAS: RangeInclusive<f32> = -100.0..=100.0;
+const MAX_STOP_SEQUENCES: usize = 8;
+
+impl SamplingParams {
+    /// Every out-of-range field, with the range it must fall in.
+    pub(crate) fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let errors = check(&mut self.clone(), false);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Pulls out-of-range values to the nearest bound (non-numbers back to
+    /// the default) and drops empty or surplus stop sequences. Returns what
+    /// was changed, in the same form `validate` reports it.
+    pub(crate) fn clamp(&mut self) -> Vec<FieldError> {
+        check(self, true)
+    }
+}
+
+fn check(params: &mut SamplingParams, fix: bool) -> Vec<FieldError> {
+    let defaults = SamplingParams::default();
+    let mut checker = Checker {
+        errors: Vec::new(),
+        fix,
+    };
+    checker.float(&mut params.temperature, TEMPERATURE, defaults.temperature, "temperature");
+    checker.float(&mut params.top_p, TOP_P, defaults.top_p, "topP");
+    checker.integer(&mut params.top_k, TOP_K, "topK");
+    checker.float(
+        &mut params.repeat_penalty,
+        REPEAT_PENALTY,
+        defaults.repeat_penalty,
+        "repeatPenalty",
+    );
+    checker.integer(&mut params.max_tokens, MAX_TOKENS, "maxTokens");
+    checker.optional_float(&mut params.min_p, MIN_P, "minP");
+    checker.optional_float(&mut params.frequency_penalty, PENALTY, "frequencyPenalty");
+    checker.optional_float(&mut params.presence_penalty, PENALTY, "presencePenalty");
+    if let Some(dry) = &mut params.dry {
+        checker.float(&mut dry.multiplier, DRY_MULTIPLIER, 0.0, "dry.multiplier");
+        checker.optional_float(&mut dry.base, DRY_BASE, "dry.base");
+        if let Some(length) = &mut dry.allowed_length {
+            checker.integer(length, DRY_ALLOWED_LENGTH, "dry.allowedLength");
+        }
+    }
+    if let Some(logit_bias) = &mut params.logit_bias {
+        let mut tokens: Vec<u32> = logit_bias.keys().copied().collect();
+        tokens.sort_unstable();
+        for token in tokens {
+            let field = format!("logitBias.{}", token);
+            let mut bias = Some(logit_bias[&token]);
+            checker.optional_float(&mut bias, LOGIT_BIAS, &field);
+            match bias {
+                Some(bias) => logit_bias.insert(token, bias),
+                None => logit_bias.remove(&token),
+            };
+        }
+    }
+    checker.stop_sequences(&mut params.stop);
+    checker.errors
+}
+
+struct Checker {
+    errors: Vec<FieldError>,
+    /// Correct each value as it is reported instead of only reporting it.
+    fix: bool,
+}
+
+impl Checker {
+    fn report(&mut self, field: &str, message: String, min: f32, max: f32) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            message,
+            min: Some(min),
+            max: Some(max),
+        });
+    }
+
+    fn float(&mut self, value: &mut f32, range: RangeInclusive<f32>, fallback: f32, field: &str) {
+        if range.contains(value) {
+            return;
+        }
+        let (min, max) = (*range.start(), *range.end());
+        let message = if value.is_finite() {
+            format!("must be between {} and {}, found {}", min, max, value)
+        } else {
+            format!("must be a number between {} and {}", min, max)
+        };
+        self.report(field, message, min, max);
+        if self.fix {
+            *value = if value.is_finite() { value.clamp(min, max) } else { fallback };
+        }
+    }
+
+    /// Like `float`, but a value that is not a number is unset rather than
+    /// reset, leaving the engine's default in place.
+    fn optional_float(
+        &mut self,
+        value: &mut Option<f32>,
+        range: RangeInclusive<f32>,
+        field: &str,
+    ) {
+        let Some(inner) = value else {
+            return;
+        };
+        let was_finite = inner.is_finite();
+        self.float(inner, range, 0.0, field);
+        if self.fix && !was_finite {
+            *value = None;
+        }
+    }
+
+    fn integer(&mut self, value: &mut u32, range: RangeInclusive<u32>, field: &str) {
+        if range.contains(value) {
+            return;
+        }
+        let (min, max) = (*range.start(), *range.end());
+        self.report(
+            field,
+            format!("must be between {} and {}, found {}", min, max, value),
+            min as f32,
+            max as f32,
+        );
+        if self.fix {
+            *value = (*value).clamp(min, max);
+        }
+    }
+
+    fn stop_sequences(&mut self, stop: &mut Vec<String>) {
+        for (index, sequence) in stop.iter().enumerate() {
+            if sequence.is_empty() {
+                self.errors.push(FieldError {
+                    field: format!("stop[{}]", index),
+                    message: "must not be empty".to_string(),
+                    min: None,
+                    max: None,
+                });
+            }
+        }
+        let count = stop.iter().filter(|sequence| !sequence.is_empty()).count();
+        if count > MAX_STOP_SEQUENCES {
+            self.report(
+                "stop",
+                format!("at most {} sequences, found {}", MAX_STOP_SEQUENCES, count),
+                0.0,
+                MAX_STOP_SEQUENCES as f32,
+            );
+        }
+        if self.fix {
+            stop.retain(|sequence| !sequence.is_empty());
+            stop.truncate(MAX_STOP_SEQUENCES);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+
+use crate::types::{DryParams, SamplingParams, SamplingPreset};
+
+const PRESETS_FILE: &str = "sampling_presets.json";
+
+/// The presets every install has, in display order. `Steady` is what a
+/// request without sampling parameters gets.
+fn builtin_presets() -> Vec<(&'static str, SamplingParams)> {
+    let steady = SamplingParams::default();
+    let dreamy = SamplingParams {
+        temperature: 0.85,
+        top_p: 0.95,
+        top_k: 80,
+        repeat_penalty: 1.05,
+        // A floor relative to the top token keeps the higher temperature
+        // from reaching for nonsense; DRY and a presence penalty keep the
+        // longer flights of fancy from looping.
+        min_p: Some(0.05),
+        presence_penalty: Some(0.3),
+        dry: Some(DryParams {
+            multiplier: 0.8,
+            base: None,
+            allowed_length: None,
+            sequence_breakers: None,
+        }),
+        ..steady.clone()
+    };
+    let deterministic = SamplingParams {
+        // Greedy decoding: the same prompt always gives the same reading.
+        temperature: 0.0,
+        top_p: 1.0,
+        top_k: 1,
+        ..steady.clone()
+    };
+    vec![
+        ("Steady", steady),
+        ("Dreamy", dreamy),
+        ("Deterministic", deterministic),
+    ]
+}
+
+/// Built-in presets followed by saved ones, by name.
+pub(crate) fn list_presets(app: &AppHandle) -> Vec<SamplingPreset> {
+    let builtins = builtin_presets()
+        .into_iter()
+        .map(|(name, params)| SamplingPreset {
+            name: name.to_string(),
+            builtin: true,
+            params,
+        });
+    let saved = load_saved(app).into_iter().map(|(name, params)| SamplingPreset {
+        name,
+        builtin: false,
+        params,
+    });
+    builtins.chain(saved).collect()
+}
+
+/// Saves `params` under `name`, replacing a saved preset of that name.
+/// Built-in presets cannot be overwritten.
+pub(crate) fn save_preset(
+    app: &AppHandle,
+    name: &str,
+    params: SamplingParams,
+) -> Result<SamplingPreset, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Preset name is empty.".to_string());
+    }
+    if builtin_presets()
+        .iter()
+        .any(|(builtin, _)| builtin.eq_ignore_ascii_case(name))
+    {
+        return Err(format!("{} is a built-in preset and cannot be replaced.", name));
+    }
+    let mut saved = load_saved(app);
+    saved.insert(name.to_string(), params.clone());
+    save_saved(app, &saved)?;
+    Ok(SamplingPreset {
+        name: name.to_string(),
+        builtin: false,
+        params,
+    })
+}
+
+fn presets_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(PRESETS_FILE))
+        .map_err(|error| format!("Unable to resolve app data directory: {}", error))
+}
+
+fn load_saved(app: &AppHandle) -> BTreeMap<String, SamplingParams> {
+    presets_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_saved(app: &AppHandle, presets: &BTreeMap<String, SamplingParams>) -> Result<(), String> {
+    let path = presets_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|error| format!("Unable to create {}: {}", parent.display(), error))?;
+    }
+    let json = serde_json::to_string_pretty(presets).map_err(|error| error.to_string())?;
+    std::fs::write(&path, json)
+        .map_err(|error| format!("Unable to write {}: {}", path.display(), error))
+}
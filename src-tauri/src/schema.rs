@@ -0,0 +1,243 @@
+use serde_json::{json, Value};
+
+use crate::dashboard::{QUARTER_LABELS, RATING_MAX, RATING_MIN, SECTION_TITLES};
+
+// JSON schemas handed to mistral.rs as decoding constraints. They mirror the
+// field mapping in docs/dashboard-schema.md and the `Reading` struct; keep them
+// in sync when either changes. Only keywords supported by mistral.rs'
+// llguidance backend are used (no `format`).
+
+const DATE_ISO_PATTERN: &str = r"^\d{4}-\d{2}-\d{2}$";
+
+fn text(min_length: usize, max_length: usize) -> Value {
+    json!({ "type": "string", "minLength": min_length, "maxLength": max_length })
+}
+
+fn integer(minimum: i64, maximum: i64) -> Value {
+    json!({ "type": "integer", "minimum": minimum, "maximum": maximum })
+}
+
+fn rating() -> Value {
+    integer(i64::from(RATING_MIN), i64::from(RATING_MAX))
+}
+
+fn string_list(min_items: usize, max_items: usize) -> Value {
+    json!({
+        "type": "array",
+        "items": text(1, 40),
+        "minItems": min_items,
+        "maxItems": max_items
+    })
+}
+
+/// Builds a closed object schema where every listed property is required.
+fn object(properties: Vec<(&str, Value)>) -> Value {
+    object_with_optional(properties, Vec::new())
+}
+
+/// Like `object`, plus properties the model may leave out.
+fn object_with_optional(required: Vec<(&str, Value)>, optional: Vec<(&str, Value)>) -> Value {
+    let required_keys: Vec<&str> = required.iter().map(|(key, _)| *key).collect();
+    let properties: serde_json::Map<String, Value> = required
+        .into_iter()
+        .chain(optional)
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required_keys,
+        "additionalProperties": false
+    })
+}
+
+fn fixed_list(item: Value, count: usize) -> Value {
+    json!({ "type": "array", "items": item, "minItems": count, "maxItems": count })
+}
+
+pub(crate) fn dashboard_json_schema() -> Value {
+    let meta = object(vec![
+        ("dateISO", json!({ "type": "string", "pattern": DATE_ISO_PATTERN })),
+        ("localeDateLabel", text(1, 40)),
+        ("generatedAtISO", text(1, 40)),
+        ("sign", text(1, 20)),
+        ("name", text(0, 80)),
+    ]);
+    let tabs = object(vec![("activeDefault", json!({ "type": "string", "enum": ["today"] }))]);
+    let today = object(vec![
+        ("headline", text(5, 80)),
+        ("subhead", text(10, 160)),
+        ("theme", text(1, 40)),
+        ("energyScore", integer(0, 100)),
+        (
+            "bestHours",
+            fixed_list(
+                object(vec![
+                    ("label", text(1, 30)),
+                    ("start", text(1, 12)),
+                    ("end", text(1, 12)),
+                ]),
+                2,
+            ),
+        ),
+        (
+            "ratings",
+            object(vec![
+                ("love", rating()),
+                ("work", rating()),
+                ("money", rating()),
+                ("health", rating()),
+            ]),
+        ),
+        (
+            "lucky",
+            object(vec![
+                ("color", text(1, 40)),
+                ("number", integer(1, 99)),
+                ("symbol", text(1, 8)),
+            ]),
+        ),
+        ("doDont", object(vec![("do", text(10, 160)), ("dont", text(10, 160))])),
+        (
+            "sections",
+            json!({
+                "type": "array",
+                "items": object(vec![
+                    ("title", json!({ "type": "string", "enum": SECTION_TITLES })),
+                    ("body", text(15, 240)),
+                ]),
+                "minItems": 4,
+                "maxItems": 4
+            }),
+        ),
+    ]);
+    let cosmic_weather = object(vec![
+        ("moon", object(vec![("phase", text(1, 30)), ("sign", text(1, 20))])),
+        (
+            "transits",
+            json!({
+                "type": "array",
+                "items": object(vec![
+                    ("title", text(1, 60)),
+                    ("tone", text(1, 20)),
+                    ("meaning", text(1, 160)),
+                ]),
+                "minItems": 1,
+                "maxItems": 3
+            }),
+        ),
+        ("affectsToday", text(10, 200)),
+    ]);
+    let compatibility = object(vec![
+        ("bestFlowWith", string_list(2, 2)),
+        ("handleGentlyWith", string_list(1, 1)),
+        (
+            "tips",
+            object(vec![("conflict", text(5, 160)), ("affection", text(5, 160))]),
+        ),
+    ]);
+    let journal_ritual = object(vec![
+        ("prompt", text(5, 160)),
+        ("starters", string_list(3, 3)),
+        ("mantra", text(5, 120)),
+        ("ritual", text(5, 160)),
+        (
+            "bestDayForDecisions",
+            object(vec![("dayLabel", text(1, 20)), ("reason", text(5, 160))]),
+        ),
+    ]);
+    let week = object(vec![
+        (
+            "arc",
+            object(vec![
+                ("start", text(5, 160)),
+                ("midweek", text(5, 160)),
+                ("weekend", text(5, 160)),
+            ]),
+        ),
+        ("keyOpportunity", text(5, 160)),
+        ("keyCaution", text(5, 160)),
+        (
+            "bestDayFor",
+            object(vec![
+                ("decisions", text(1, 20)),
+                ("conversations", text(1, 20)),
+                ("rest", text(1, 20)),
+            ]),
+        ),
+    ]);
+    let month = object(vec![
+        ("theme", text(5, 120)),
+        (
+            "keyDates",
+            fixed_list(
+                object(vec![
+                    ("dateLabel", text(1, 20)),
+                    ("title", text(1, 60)),
+                    ("note", text(1, 160)),
+                ]),
+                3,
+            ),
+        ),
+        (
+            "newMoon",
+            object(vec![("dateLabel", text(1, 20)), ("intention", text(5, 160))]),
+        ),
+        (
+            "fullMoon",
+            object(vec![("dateLabel", text(1, 20)), ("release", text(5, 160))]),
+        ),
+        ("oneThing", text(5, 160)),
+    ]);
+    let year = object(vec![
+        ("headline", text(5, 160)),
+        (
+            "quarters",
+            fixed_list(
+                object(vec![
+                    ("label", json!({ "type": "string", "enum": QUARTER_LABELS })),
+                    ("focus", text(5, 160)),
+                ]),
+                4,
+            ),
+        ),
+        ("powerMonths", string_list(1, 3)),
+        (
+            "challengeMonth",
+            object(vec![("month", text(1, 20)), ("guidance", text(5, 160))]),
+        ),
+    ]);
+
+    // Only the sections the UI renders are required, matching
+    // `DashboardPayload`; the rest may be left out.
+    object_with_optional(
+        vec![
+            ("meta", meta),
+            ("today", today),
+            ("compatibility", compatibility),
+        ],
+        vec![
+            ("tabs", tabs),
+            ("cosmicWeather", cosmic_weather),
+            ("journalRitual", journal_ritual),
+            ("week", week),
+            ("month", month),
+            ("year", year),
+        ],
+    )
+}
+
+pub(crate) fn reading_json_schema() -> Value {
+    object(vec![
+        ("date", json!({ "type": "string", "pattern": DATE_ISO_PATTERN })),
+        ("sign", text(1, 20)),
+        ("title", text(5, 80)),
+        ("message", text(20, 600)),
+        ("themes", string_list(3, 3)),
+        ("affirmation", text(5, 160)),
+        ("luckyColor", text(1, 40)),
+        ("luckyNumber", integer(1, 99)),
+        ("createdAt", text(1, 40)),
+        ("source", json!({ "type": "string", "enum": ["model"] })),
+    ])
+}
@@ -1,6 +1,11 @@
 use chrono::Datelike;
-use serde_json::json;
 
+use crate::dashboard::{
+    ChallengeMonth, Compatibility, CompatibilityTips, CosmicWeather, DashboardMeta,
+    DashboardPayload, DashboardTabs, DecisionDay, DoDont, FullMoon, HourWindow, JournalRitual,
+    KeyDate, Lucky, MonthOutlook, Moon, NewMoon, Quarter, Ratings, Section, TodayPanel, Transit,
+    WeekArc, WeekBestDays, WeekOutlook, YearOutlook,
+};
 use crate::types::{Reading, ReadingRequest};
 
 pub(crate) fn generate_stub_reading(request: &ReadingRequest) -> Reading {
@@ -14,103 +19,31 @@ pub(crate) fn generate_stub_reading(request: &ReadingRequest) -> Reading {
         request.profile.personality
     ));
     let mut rng = SeededRng::new(seed);
+    let vocabulary = &request.persona.stub;
+    let message = stub_message(&mut rng, request, &sign);
 
-    let titles = [
-        "The hush before a bright idea",
-        "Soft focus, clear intention",
-        "A horizon you can trust",
-        "The spark beneath stillness",
-        "A graceful return to center",
-    ];
-    let openings = vec![
-        format!(
-            "Today opens with a {} current that invites gentler choices.",
-            request.profile.mood.to_lowercase()
-        ),
-        format!(
-            "The day moves at a {} pace, offering room to breathe.",
-            request.profile.mood.to_lowercase()
-        ),
-        format!(
-            "You may notice a {} undertone guiding your timing.",
-            request.profile.mood.to_lowercase()
-        ),
-    ];
-    let middles = vec![
-        format!(
-            "As a {}, you naturally notice patterns others miss, so trust what quietly repeats.",
-            request.profile.personality
-        ),
-        format!(
-            "Your {} instincts highlight what is worth protecting and what can soften.",
-            request.profile.personality.to_lowercase()
-        ),
-        format!(
-            "The {} in you is ready to translate intuition into a simple next step.",
-            request.profile.personality.to_lowercase()
-        ),
-    ];
-    let closers = [
-        "Let small rituals ground you, and remember that clarity arrives in layers, not lightning bolts.",
-        "If you pause before responding, the right phrasing will rise on its own.",
-        "Choose one gentle action that honors your energy, and let that be enough.",
-    ];
-
-    let message = format!(
-        "{} {} {}",
-        pick_string(&mut rng, &openings),
-        pick_string(&mut rng, &middles),
-        pick(&mut rng, &closers)
-    );
-
-    let mut themes = vec![
-        "Quiet confidence",
-        "Meaningful timing",
-        "Boundaries with kindness",
-        "Creative listening",
-        "Soft courage",
-        "Steady focus",
-    ];
+    let mut themes: Vec<&str> = vocabulary.themes.iter().map(String::as_str).collect();
     shuffle(&mut rng, &mut themes);
 
     Reading {
         date: request.date.clone(),
         sign,
-        title: pick(&mut rng, &titles).to_string(),
+        title: pick_string(&mut rng, &vocabulary.titles),
         message,
         themes: [
             themes[0].to_string(),
             themes[1].to_string(),
             themes[2].to_string(),
         ],
-        affirmation: pick(
-            &mut rng,
-            &[
-                "I meet today with grounded curiosity.",
-                "I can move gently and still be powerful.",
-                "My inner compass grows clearer with every breath.",
-                "I honor what I feel and choose what I need.",
-            ],
-        )
-        .to_string(),
-        lucky_color: pick(
-            &mut rng,
-            &[
-                "Moonlit Indigo",
-                "Starlight Silver",
-                "Luminous Lavender",
-                "Sea-glass Teal",
-                "Amber Mist",
-            ],
-        )
-        .to_string(),
+        affirmation: pick_string(&mut rng, &vocabulary.affirmations),
+        lucky_color: pick_string(&mut rng, &vocabulary.lucky_colors),
         lucky_number: (rng.next() * 9.0).floor() as u8 + 1,
         created_at: chrono::Utc::now().to_rfc3339(),
         source: "stub".to_string(),
     }
 }
 
-pub(crate) fn generate_stub_dashboard(request: &ReadingRequest) -> serde_json::Value {
+pub(crate) fn generate_stub_dashboard(request: &ReadingRequest) -> DashboardPayload {
     let sign = zodiac_sign(&request.profile.birthdate);
     let seed = seeded_hash(&format!(
         "{}-{}-{}-{}-{}",
@@ -121,181 +54,177 @@ pub(crate) fn generate_stub_dashboard(request: &ReadingRequest) -> serde_json::V
         request.profile.personality
     ));
     let mut rng = SeededRng::new(seed);
+    let vocabulary = &request.persona.stub;
 
-    let title = pick(
-        &mut rng,
-        &[
-            "Soft focus, clear intention",
-            "The hush before a bright idea",
-            "A horizon you can trust",
-            "The spark beneath stillness",
-            "A graceful return to center",
-        ],
-    );
-    let openings = vec![
-        format!(
-            "The day opens with a {} current that invites gentler choices.",
-            request.profile.mood.to_lowercase()
-        ),
-        format!(
-            "A {} undertone guides your timing and attention.",
-            request.profile.mood.to_lowercase()
-        ),
-        format!(
-            "You move through a {} rhythm that rewards patience.",
-            request.profile.mood.to_lowercase()
-        ),
-    ];
-    let middles = vec![
-        format!(
-            "As {}, your {} nature notices subtle shifts first.",
-            sign,
-            request.profile.personality.to_lowercase()
-        ),
-        format!(
-            "Your {} instincts highlight what wants to soften.",
-            request.profile.personality.to_lowercase()
-        ),
-        format!(
-            "The {} in you translates intuition into one clear step.",
-            request.profile.personality.to_lowercase()
-        ),
-    ];
-    let closers = vec![
-        "Let small rituals ground you, and let clarity arrive in layers.".to_string(),
-        "Pause before replying and your best phrasing will surface.".to_string(),
-        "Choose one gentle action that honors your energy, and let that be enough.".to_string(),
-    ];
-    let message = format!(
-        "{} {} {}",
-        pick_string(&mut rng, &openings),
-        pick_string(&mut rng, &middles),
-        pick_string(&mut rng, &closers)
-    );
+    let title = pick_string(&mut rng, &vocabulary.titles);
+    let message = stub_message(&mut rng, request, &sign);
 
     let date_label = chrono::NaiveDate::parse_from_str(&request.date, "%Y-%m-%d")
         .map(|date| date.format("%A, %B %-d").to_string())
         .unwrap_or_else(|_| request.date.clone());
 
-    json!({
-        "meta": {
-            "dateISO": request.date.clone(),
-            "localeDateLabel": date_label,
-            "generatedAtISO": chrono::Utc::now().to_rfc3339(),
-            "sign": sign,
-            "name": request.profile.name.clone()
-        },
-        "tabs": {
-            "activeDefault": "today"
+    DashboardPayload {
+        meta: DashboardMeta {
+            date_iso: request.date.clone(),
+            locale_date_label: date_label,
+            generated_at_iso: chrono::Utc::now().to_rfc3339(),
+            sign,
+            name: request.profile.name.clone(),
         },
-        "today": {
-            "headline": title,
-            "subhead": message,
-            "theme": pick(&mut rng, &["Clarity", "Patience", "Warmth", "Alignment", "Ease"]),
-            "energyScore": (rng.next() * 45.0).floor() as u8 + 55,
-            "bestHours": [
-                { "label": "Morning", "start": "9:00 AM", "end": "11:00 AM" },
-                { "label": "Evening", "start": "5:00 PM", "end": "7:00 PM" }
+        tabs: Some(DashboardTabs {
+            active_default: "today".to_string(),
+        }),
+        today: TodayPanel {
+            headline: title,
+            subhead: message,
+            theme: pick_string(&mut rng, &vocabulary.themes),
+            energy_score: (rng.next() * 45.0).floor() as u32 + 55,
+            best_hours: vec![
+                hour_window("Morning", "9:00 AM", "11:00 AM"),
+                hour_window("Evening", "5:00 PM", "7:00 PM"),
             ],
-            "ratings": {
-                "love": (rng.next() * 3.0).floor() as u8 + 3,
-                "work": (rng.next() * 3.0).floor() as u8 + 3,
-                "money": (rng.next() * 3.0).floor() as u8 + 2,
-                "health": (rng.next() * 3.0).floor() as u8 + 3
+            ratings: Ratings {
+                love: (rng.next() * 3.0).floor() as u32 + 3,
+                work: (rng.next() * 3.0).floor() as u32 + 3,
+                money: (rng.next() * 3.0).floor() as u32 + 2,
+                health: (rng.next() * 3.0).floor() as u32 + 3,
             },
-            "lucky": {
-                "color": pick(&mut rng, &["Gold", "Moonlit Indigo", "Soft Lavender", "Sea-glass Teal"]),
-                "number": (rng.next() * 9.0).floor() as u8 + 1,
-                "symbol": pick(&mut rng, &["★", "☾", "✦"])
+            lucky: Lucky {
+                color: pick_string(&mut rng, &vocabulary.lucky_colors),
+                number: (rng.next() * 9.0).floor() as u32 + 1,
+                symbol: pick(&mut rng, &["★", "☾", "✦"]).to_string(),
             },
-            "doDont": {
-                "do": "Trust your instincts and keep plans simple.",
-                "dont": "Overshare or rush to fill quiet moments."
+            do_dont: DoDont {
+                action: "Trust your instincts and keep plans simple.".to_string(),
+                avoid: "Overshare or rush to fill quiet moments.".to_string(),
             },
-            "sections": [
-                { "title": "Focus", "body": "Pick one clear priority and let the rest soften." },
-                { "title": "Relationships", "body": "Lead with warmth and give others space to respond." },
-                { "title": "Action", "body": "Take one grounded step that supports your long view." },
-                { "title": "Reflection", "body": "Notice what feels steady and keep returning to it." }
-            ]
+            sections: vec![
+                section("Focus", "Pick one clear priority and let the rest soften."),
+                section("Relationships", "Lead with warmth and give others space to respond."),
+                section("Action", "Take one grounded step that supports your long view."),
+                section("Reflection", "Notice what feels steady and keep returning to it."),
+            ],
         },
-        "cosmicWeather": {
-            "moon": {
-                "phase": pick(&mut rng, &["First Quarter", "Waxing Crescent", "Full Moon", "New Moon"]),
-                "sign": pick(&mut rng, &["Cancer", "Libra", "Scorpio", "Taurus"])
+        cosmic_weather: Some(CosmicWeather {
+            moon: Moon {
+                phase: pick(&mut rng, &["First Quarter", "Waxing Crescent", "Full Moon", "New Moon"]).to_string(),
+                sign: pick(&mut rng, &["Cancer", "Libra", "Scorpio", "Taurus"]).to_string(),
             },
-            "transits": [
-                {
-                    "title": "Mercury review cycle",
-                    "tone": "neutral",
-                    "meaning": "Double-check details before committing."
+            transits: vec![
+                Transit {
+                    title: "Mercury review cycle".to_string(),
+                    tone: "neutral".to_string(),
+                    meaning: "Double-check details before committing.".to_string(),
+                },
+                Transit {
+                    title: "Venus harmony".to_string(),
+                    tone: "soft".to_string(),
+                    meaning: "Gentle conversations land with ease.".to_string(),
                 },
-                {
-                    "title": "Venus harmony",
-                    "tone": "soft",
-                    "meaning": "Gentle conversations land with ease."
-                }
             ],
-            "affectsToday": "Emotional tides rise and fall; choose calm responses."
-        },
-        "compatibility": {
-            "bestFlowWith": ["Aries", "Gemini"],
-            "handleGentlyWith": ["Taurus"],
-            "tips": {
-                "conflict": "Pause before replying to keep things kind.",
-                "affection": "Playful honesty keeps the mood light."
-            }
-        },
-        "journalRitual": {
-            "prompt": "What feels most important to protect today?",
-            "starters": ["I feel…", "I need…", "I'm avoiding…"],
-            "mantra": "I move with grace and clear intention.",
-            "ritual": "Light a candle and name one priority out loud.",
-            "bestDayForDecisions": {
-                "dayLabel": "Thursday",
-                "reason": "Clarity peaks in the afternoon."
-            }
-        },
-        "week": {
-            "arc": {
-                "start": "Settle into a calm, focused rhythm.",
-                "midweek": "Tune inward before making changes.",
-                "weekend": "Conversations flow and ease returns."
+            affects_today: "Emotional tides rise and fall; choose calm responses.".to_string(),
+        }),
+        compatibility: Compatibility {
+            best_flow_with: strings(&["Aries", "Gemini"]),
+            handle_gently_with: strings(&["Taurus"]),
+            tips: CompatibilityTips {
+                conflict: "Pause before replying to keep things kind.".to_string(),
+                affection: "Playful honesty keeps the mood light.".to_string(),
             },
-            "keyOpportunity": "Strengthen a bond through simple honesty.",
-            "keyCaution": "Avoid overcommitting before you feel ready.",
-            "bestDayFor": {
-                "decisions": "Thursday",
-                "conversations": "Wednesday",
-                "rest": "Sunday"
-            }
         },
-        "month": {
-            "theme": "Clarity through gentle structure.",
-            "keyDates": [
-                { "dateLabel": "Jan 9–10", "title": "New Moon", "note": "Set intentions around focus." },
-                { "dateLabel": "Jan 17", "title": "Personal reset", "note": "Simplify a lingering task." },
-                { "dateLabel": "Jan 25", "title": "Full Moon", "note": "Release what feels heavy." }
+        journal_ritual: Some(JournalRitual {
+            prompt: "What feels most important to protect today?".to_string(),
+            starters: strings(&["I feel…", "I need…", "I'm avoiding…"]),
+            mantra: "I move with grace and clear intention.".to_string(),
+            ritual: "Light a candle and name one priority out loud.".to_string(),
+            best_day_for_decisions: DecisionDay {
+                day_label: "Thursday".to_string(),
+                reason: "Clarity peaks in the afternoon.".to_string(),
+            },
+        }),
+        week: Some(WeekOutlook {
+            arc: WeekArc {
+                start: "Settle into a calm, focused rhythm.".to_string(),
+                midweek: "Tune inward before making changes.".to_string(),
+                weekend: "Conversations flow and ease returns.".to_string(),
+            },
+            key_opportunity: "Strengthen a bond through simple honesty.".to_string(),
+            key_caution: "Avoid overcommitting before you feel ready.".to_string(),
+            best_day_for: WeekBestDays {
+                decisions: "Thursday".to_string(),
+                conversations: "Wednesday".to_string(),
+                rest: "Sunday".to_string(),
+            },
+        }),
+        month: Some(MonthOutlook {
+            theme: "Clarity through gentle structure.".to_string(),
+            key_dates: vec![
+                key_date("Jan 9–10", "New Moon", "Set intentions around focus."),
+                key_date("Jan 17", "Personal reset", "Simplify a lingering task."),
+                key_date("Jan 25", "Full Moon", "Release what feels heavy."),
             ],
-            "newMoon": { "dateLabel": "Jan 9–10", "intention": "Commit to one steady practice." },
-            "fullMoon": { "dateLabel": "Jan 25", "release": "Let go of scattered priorities." },
-            "oneThing": "If you do one thing, choose the gentlest next step."
-        },
-        "year": {
-            "headline": "A year to trust your timing and refine your craft.",
-            "quarters": [
-                { "label": "Q1", "focus": "Grounded beginnings and clearing space." },
-                { "label": "Q2", "focus": "Momentum builds through collaboration." },
-                { "label": "Q3", "focus": "Visibility grows with steady effort." },
-                { "label": "Q4", "focus": "Integration and graceful completion." }
+            new_moon: NewMoon {
+                date_label: "Jan 9–10".to_string(),
+                intention: "Commit to one steady practice.".to_string(),
+            },
+            full_moon: FullMoon {
+                date_label: "Jan 25".to_string(),
+                release: "Let go of scattered priorities.".to_string(),
+            },
+            one_thing: "If you do one thing, choose the gentlest next step.".to_string(),
+        }),
+        year: Some(YearOutlook {
+            headline: "A year to trust your timing and refine your craft.".to_string(),
+            quarters: vec![
+                quarter("Q1", "Grounded beginnings and clearing space."),
+                quarter("Q2", "Momentum builds through collaboration."),
+                quarter("Q3", "Visibility grows with steady effort."),
+                quarter("Q4", "Integration and graceful completion."),
             ],
-            "powerMonths": ["March", "July"],
-            "challengeMonth": { "month": "October", "guidance": "Slow down and streamline." }
-        }
-    })
+            power_months: strings(&["March", "July"]),
+            challenge_month: ChallengeMonth {
+                month: "October".to_string(),
+                guidance: "Slow down and streamline.".to_string(),
+            },
+        }),
+    }
+}
+
+fn hour_window(label: &str, start: &str, end: &str) -> HourWindow {
+    HourWindow {
+        label: label.to_string(),
+        start: start.to_string(),
+        end: end.to_string(),
+    }
+}
+
+fn section(title: &str, body: &str) -> Section {
+    Section {
+        title: title.to_string(),
+        body: body.to_string(),
+    }
+}
+
+fn key_date(date_label: &str, title: &str, note: &str) -> KeyDate {
+    KeyDate {
+        date_label: date_label.to_string(),
+        title: title.to_string(),
+        note: note.to_string(),
+    }
+}
+
+fn quarter(label: &str, focus: &str) -> Quarter {
+    Quarter {
+        label: label.to_string(),
+        focus: focus.to_string(),
+    }
 }
 
-fn zodiac_sign(date: &str) -> String {
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+pub(crate) fn zodiac_sign(date: &str) -> String {
     let parsed = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d");
     if let Ok(date) = parsed {
         let month = date.month();
@@ -361,6 +290,21 @@ impl SeededRng {
     }
 }
 
+/// Opening, middle and closer from the persona's vocabulary, with the
+/// profile filled into the templates.
+fn stub_message(rng: &mut SeededRng, request: &ReadingRequest, sign: &str) -> String {
+    let vocabulary = &request.persona.stub;
+    let fill = |template: String| {
+        template
+            .replace("{mood}", &request.profile.mood.to_lowercase())
+            .replace("{personality}", &request.profile.personality.to_lowercase())
+            .replace("{sign}", sign)
+    };
+    let opening = fill(pick_string(rng, &vocabulary.openings));
+    let middle = fill(pick_string(rng, &vocabulary.middles));
+    format!("{} {} {}", opening, middle, pick_string(rng, &vocabulary.closers))
+}
+
 fn pick<'a>(rng: &mut SeededRng, values: &'a [&str]) -> &'a str {
     let index = (rng.next() * values.len() as f32).floor() as usize;
     values[index % values.len()]
@@ -1,10 +1,24 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(tag = "status", rename_all = "snake_case")]
 pub enum ModelStatus {
     Unloaded,
-    Loading { progress: f32 },
+    Loading {
+        progress: f32,
+        stage: LoadStage,
+        #[serde(rename = "etaMs")]
+        eta_ms: Option<u64>,
+        #[serde(rename = "bytesDone")]
+        bytes_done: u64,
+        #[serde(rename = "bytesTotal")]
+        bytes_total: u64,
+        /// The stage gives no measure of how far along it is (the mistral.rs
+        /// build); `progress` holds at its start until it ends.
+        indeterminate: bool,
+    },
     Loaded {
         #[serde(rename = "modelPath")]
         model_path: String,
@@ -12,8 +26,143 @@ pub enum ModelStatus {
         model_size_mb: f32,
         #[serde(rename = "modelSizeBytes")]
         model_size_bytes: u64,
+        /// Header metadata, absent if the header could not be parsed.
+        #[serde(rename = "modelInfo")]
+        model_info: Option<GgufInfo>,
+        /// `embedded` (mistral.rs in-process) or `openai` (a local HTTP server,
+        /// in which case `modelPath` is the server URL and model name).
+        backend: String,
     },
+    /// Waiting for in-flight generations before the model is dropped.
+    Unloading,
     Error { message: String },
+    /// Built without the `mistral` feature: there is no engine to load, and
+    /// every reading comes from the stub.
+    EngineUnavailable { message: String },
+    /// The model file failed verification against its checksum manifest.
+    Corrupt {
+        #[serde(rename = "modelPath")]
+        model_path: String,
+        message: String,
+        #[serde(rename = "expectedSha256")]
+        expected_sha256: String,
+        /// Absent when the size check alone showed the file is wrong.
+        #[serde(rename = "actualSha256")]
+        actual_sha256: Option<String>,
+        #[serde(rename = "expectedBytes")]
+        expected_bytes: u64,
+        #[serde(rename = "actualBytes")]
+        actual_bytes: u64,
+    },
+}
+
+/// Steps of a model load, in order. `Building` and `BuildingFullPath` are the
+/// two mistral.rs attempts (directory + filename, then the full path); each
+/// covers tokenizer construction and tensor materialisation. mistral.rs does
+/// both inside a single `build()` that reports nothing back, so there is no
+/// separate tokenizer stage.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadStage {
+    Locating,
+    /// Reading the file through its checksum manifest; skipped without one.
+    Verifying,
+    Building,
+    BuildingFullPath,
+}
+
+/// What the GGUF header says about a model, read without loading weights.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GgufInfo {
+    pub version: u32,
+    pub tensor_count: u64,
+    pub name: Option<String>,
+    pub architecture: Option<String>,
+    pub parameter_count: u64,
+    pub quantization: Option<String>,
+    pub context_length: Option<u64>,
+    pub block_count: Option<u64>,
+    pub embedding_length: Option<u64>,
+    pub head_count: Option<u64>,
+    pub head_count_kv: Option<u64>,
+    pub chat_template: Option<String>,
+    pub tokenizer_model: Option<String>,
+    pub license: Option<String>,
+}
+
+/// A GGUF file found by the model registry.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelEntry {
+    pub name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub last_used_at: Option<String>,
+    pub active: bool,
+}
+
+/// Payload of `model:import` events.
+#[derive(Clone, Serialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ImportEvent {
+    Copying {
+        #[serde(rename = "sourcePath")]
+        source_path: String,
+        progress: f32,
+        #[serde(rename = "bytesDone")]
+        bytes_done: u64,
+        #[serde(rename = "bytesTotal")]
+        bytes_total: u64,
+    },
+    Imported {
+        entry: ModelEntry,
+        /// Hard-linked rather than copied.
+        linked: bool,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+/// Outcome of the pre-load memory check.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryVerdict {
+    /// Fits in available RAM.
+    Fits,
+    /// Fits only by pushing into swap; loading proceeds but may crawl.
+    Tight,
+    /// Exceeds available RAM plus free swap; loading is refused.
+    Insufficient,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryCheck {
+    pub model_path: String,
+    pub verdict: MemoryVerdict,
+    pub required_bytes: u64,
+    /// What the same model would need with low-memory mode on.
+    pub low_memory_required_bytes: u64,
+    pub available_bytes: u64,
+    pub free_swap_bytes: u64,
+    pub low_memory_mode: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemResources {
+    pub total_memory_bytes: u64,
+    pub available_memory_bytes: u64,
+    pub total_swap_bytes: u64,
+    pub free_swap_bytes: u64,
+    pub cpu_count: usize,
+    pub low_memory_mode: bool,
+    /// The most recent pre-load check, or one for the path passed to
+    /// `system_resources`.
+    pub memory_check: Option<MemoryCheck>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -39,8 +188,10 @@ pub struct Reading {
     pub source: String,
 }
 
+/// Fields left out take their defaults, so a persona file or preset can set
+/// only what it changes.
 #[derive(Clone, Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct SamplingParams {
     pub temperature: f32,
     pub top_p: f32,
@@ -49,6 +200,77 @@ pub struct SamplingParams {
     pub max_tokens: u32,
     pub seed: Option<u32>,
     pub stop: Vec<String>,
+    /// Deadline for this generation, re-prompts included. Capped by the
+    /// backend's ceiling; the command's default applies when unset.
+    pub timeout_ms: Option<u64>,
+    /// Drops tokens less likely than this fraction of the top token.
+    pub min_p: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+    pub presence_penalty: Option<f32>,
+    pub dry: Option<DryParams>,
+    /// Added to the logits of the given token IDs; `-100` all but bans one.
+    pub logit_bias: Option<HashMap<u32, f32>>,
+}
+
+/// "Don't repeat yourself" sampling: penalises tokens that would extend a
+/// sequence already seen in the context. Unset fields take the engine's
+/// defaults.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DryParams {
+    pub multiplier: f32,
+    pub base: Option<f32>,
+    /// Repeats up to this length go unpenalised.
+    pub allowed_length: Option<u32>,
+    /// Strings that end a sequence, such as newlines and quotes.
+    pub sequence_breakers: Option<Vec<String>>,
+}
+
+/// A named set of sampling parameters. Built-in presets ship with the app;
+/// the rest were saved with `save_sampling_preset`.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingPreset {
+    pub name: String,
+    pub builtin: bool,
+    pub params: SamplingParams,
+}
+
+/// A voice readings can be written in. Built-in personas ship with the app;
+/// the rest are JSON files in the `personas` config directory.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Persona {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Who the model is and how it writes. The JSON output rules are added
+    /// by the backends and do not belong here.
+    pub system_prompt: String,
+    /// Used when a request brings no sampling parameters of its own.
+    #[serde(default)]
+    pub sampling: SamplingParams,
+    #[serde(default)]
+    pub stub: StubVocabulary,
+    #[serde(default, skip_deserializing)]
+    pub builtin: bool,
+}
+
+/// Word lists the offline generator picks from; empty lists fall back to
+/// the default persona's.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StubVocabulary {
+    pub titles: Vec<String>,
+    /// First sentence of the message; `{mood}` is replaced with the mood.
+    pub openings: Vec<String>,
+    /// Second sentence; `{personality}` and `{sign}` are replaced.
+    pub middles: Vec<String>,
+    pub closers: Vec<String>,
+    pub themes: Vec<String>,
+    pub affirmations: Vec<String>,
+    pub lucky_colors: Vec<String>,
 }
 
 impl Default for SamplingParams {
@@ -62,6 +284,12 @@ impl Default for SamplingParams {
             max_tokens: 3600,
             seed: None,
             stop: vec![],
+            timeout_ms: None,
+            min_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            dry: None,
+            logit_bias: None,
         }
     }
 }
@@ -73,6 +301,38 @@ pub struct ReadingRequest {
     pub prompt: Option<String>,
     #[serde(default)]
     pub sampling: SamplingParams,
+    /// Resolved from the command's `persona` ID, never sent by the frontend.
+    #[serde(skip)]
+    pub persona: Persona,
+}
+
+/// How urgently a generation should run. Interactive requests (the user is
+/// waiting on the result) are admitted before background prefetches.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationPriority {
+    #[default]
+    Interactive,
+    Background,
+}
+
+/// Payload of `model:queue` events.
+#[derive(Clone, Serialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum QueueEvent {
+    /// Waiting behind other generations; `position` starts at 1.
+    Queued {
+        #[serde(rename = "requestId")]
+        request_id: String,
+        position: usize,
+        priority: GenerationPriority,
+    },
+    Started {
+        #[serde(rename = "requestId")]
+        request_id: String,
+        #[serde(rename = "waitedMs")]
+        waited_ms: u64,
+    },
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -82,3 +342,176 @@ pub enum StreamEvent {
     Chunk { chunk: String },
     End,
 }
+
+/// What a `generate_*` command returns: the payload plus where it came from,
+/// so the UI can say when, and why, a reading is the offline fallback.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationOutcome<T> {
+    pub payload: T,
+    /// `model` or `stub`.
+    pub source: String,
+    pub fallback: bool,
+    pub fallback_reason: Option<FallbackReason>,
+    /// The loaded model the generation ran against, even when it fell back.
+    pub model: Option<String>,
+    /// Model calls made, re-prompts included.
+    pub attempts: u32,
+    /// From the command being invoked to the payload being ready, queueing
+    /// included.
+    pub latency_ms: u64,
+    /// The template the prompt was rendered from; `None` when the caller
+    /// supplied its own prompt.
+    pub prompt_template: Option<PromptStamp>,
+    /// Profile fields that were cleaned before reaching the prompt.
+    pub sanitized: Vec<SanitizedField>,
+}
+
+/// A user-provided field that was changed before use, and how.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SanitizedField {
+    pub field: String,
+    pub actions: Vec<SanitizeAction>,
+}
+
+#[derive(Clone, Copy, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SanitizeAction {
+    /// Longer than the field's limit.
+    Truncated,
+    /// Control characters or prompt delimiters removed.
+    CharactersStripped,
+    /// Read like an instruction to the model and was replaced with a neutral
+    /// value.
+    InstructionNeutralized,
+}
+
+/// Which template, at which version, produced a prompt.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptStamp {
+    pub name: String,
+    pub version: u32,
+    /// Whether this template or any it includes came from an override file.
+    pub overridden: bool,
+    /// Included templates as `name@version`.
+    pub includes: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplateInfo {
+    pub name: String,
+    pub version: u32,
+    pub overridden: bool,
+    /// The override file, for overridden templates.
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FallbackReason {
+    /// The watchdog's deadline passed.
+    TimedOut { message: String },
+    /// The model kept producing JSON that does not parse.
+    InvalidOutput { message: String },
+    /// The prompt left too little of the context window for the output.
+    ContextOverflow { message: String },
+    InferenceFailed { message: String },
+}
+
+/// How much of the model's context window a prompt takes.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenEstimate {
+    pub prompt_tokens: u32,
+    /// Counted by the model's tokenizer rather than estimated from length.
+    pub exact: bool,
+    pub context_length: Option<u32>,
+    /// Room left for output; `None` when the context length is unknown.
+    pub available_tokens: Option<u32>,
+}
+
+/// One model call, as emitted on `model:metrics`. Re-prompts are separate
+/// calls with a higher `attempt`.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InferenceMetrics {
+    pub request_id: String,
+    /// `reading`, `reading_stream` or `dashboard`.
+    pub kind: String,
+    pub attempt: u32,
+    pub prompt_tokens: Option<u32>,
+    pub completion_tokens: Option<u32>,
+    pub time_to_first_token_ms: Option<u64>,
+    pub duration_ms: u64,
+    pub tokens_per_second: Option<f32>,
+    pub prompt_tokens_per_second: Option<f32>,
+    /// As reported by the engine; `None` when it does not say.
+    pub prefix_cache_hit: Option<bool>,
+    /// The engine's (`stop`, `length`), or `timeout`, `cancelled`,
+    /// `context_overflow` or `error` for a call that produced nothing.
+    pub finish_reason: Option<String>,
+    pub max_tokens: u32,
+    /// Cut off by `max_tokens` rather than finished by the model.
+    pub truncated: bool,
+}
+
+/// Totals for one kind of call since launch.
+#[derive(Clone, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSummary {
+    pub kind: String,
+    pub calls: u32,
+    pub truncated_calls: u32,
+    pub prefix_cache_hits: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub max_completion_tokens: u32,
+    pub mean_time_to_first_token_ms: Option<u64>,
+    pub mean_tokens_per_second: Option<f32>,
+}
+
+/// Returned by `model_metrics`.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelMetrics {
+    pub summaries: Vec<MetricsSummary>,
+    /// The latest calls, oldest first.
+    pub recent: Vec<InferenceMetrics>,
+}
+
+/// A single constraint violation, addressed by its JSON path (e.g. `today.bestHours`).
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+    /// The allowed range, for fields that have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f32>,
+}
+
+/// Error returned by the `generate_*` commands. Cancellation is reported
+/// separately so the UI does not mistake it for a failure to fall back from.
+#[derive(Clone, Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GenerationError {
+    Cancelled {
+        #[serde(rename = "requestId")]
+        request_id: String,
+    },
+    Failed { message: String },
+    /// The payload did not satisfy the documented schema constraints.
+    InvalidPayload { errors: Vec<FieldError> },
+    /// Sampling parameters out of range; only returned when not lenient.
+    InvalidSampling { errors: Vec<FieldError> },
+}
+
+impl From<String> for GenerationError {
+    fn from(message: String) -> Self {
+        GenerationError::Failed { message }
+    }
+}
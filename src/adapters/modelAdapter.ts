@@ -1,27 +1,67 @@
 import { invoke } from "@tauri-apps/api/core";
 import { debugModelLog } from "../debug/logger";
-import type { ProfileDraft, SamplingParams } from "../domain/types";
+import type {
+  GenerationOutcome,
+  OutcomeSummary,
+  ProfileDraft,
+  SamplingParams,
+  TokenEstimate,
+} from "../domain/types";
+
+export interface GeneratedPayload {
+  payloadJson: string;
+  // Null when the frontend's stub produced the payload.
+  outcome: OutcomeSummary | null;
+}
 
 export interface HoroscopeAdapter {
   generate(
     profile: ProfileDraft,
     date: string,
     prompt: string | undefined,
-    sampling: SamplingParams
-  ): Promise<string>;
+    sampling?: SamplingParams,
+    persona?: string,
+    seed?: number
+  ): Promise<GeneratedPayload>;
+}
+
+// The backend rejects a cancelled generation with `{ kind: "cancelled" }`.
+export function isCancelledGeneration(error: unknown): boolean {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    (error as { kind?: unknown }).kind === "cancelled"
+  );
 }
 
 export class EmbeddedModelAdapter implements HoroscopeAdapter {
+  // Only one dashboard generation is useful at a time; a newer request
+  // (e.g. after the profile changed) cancels the one still running.
+  private static activeRequestId: string | null = null;
+
+  static cancelActive() {
+    const requestId = EmbeddedModelAdapter.activeRequestId;
+    if (!requestId) return;
+    debugModelLog("log", "adapter:model:cancel", { requestId });
+    void invoke<string[]>("cancel_generation", { requestId }).catch((error) => {
+      debugModelLog("warn", "adapter:model:cancel:failed", error);
+    });
+  }
+
   async generate(
     profile: ProfileDraft,
     date: string,
     prompt: string | undefined,
-    sampling: SamplingParams
-  ) {
+    sampling?: SamplingParams,
+    persona?: string,
+    seed?: number
+  ): Promise<GeneratedPayload> {
     debugModelLog("log", "adapter:model:invoke", {
       date,
       hasPrompt: Boolean(prompt),
       sampling,
+      seed,
+      persona,
       profile: {
         name: profile.name,
         birthdate: profile.birthdate,
@@ -29,23 +69,63 @@ export class EmbeddedModelAdapter implements HoroscopeAdapter {
         personality: profile.personality,
       },
     });
+    if (prompt) {
+      // The backend clamps maxTokens to fit; this only surfaces it early, so
+      // the generation neither waits for it nor fails with it.
+      void invoke<TokenEstimate>("estimate_tokens", { prompt, persona })
+        .then((estimate) => {
+          const fits =
+            !sampling ||
+            estimate.availableTokens === null ||
+            estimate.availableTokens >= sampling.maxTokens;
+          debugModelLog(fits ? "log" : "warn", "adapter:model:budget", {
+            ...estimate,
+            maxTokens: sampling?.maxTokens ?? null,
+          });
+        })
+        .catch((error) => {
+          debugModelLog("warn", "adapter:model:budget:failed", error);
+        });
+    }
+    const requestId = `dashboard-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`;
+    EmbeddedModelAdapter.activeRequestId = requestId;
     try {
-      const payloadJson = await invoke<string>("generate_dashboard_payload", {
+      // Rust deserializes and validates the payload; the pipeline still
+      // consumes JSON text so it can share the stub/validation path.
+      const outcome = await invoke<GenerationOutcome<unknown>>("generate_dashboard_payload", {
         profile,
         date,
         prompt,
         sampling,
+        seed,
+        persona,
+        requestId,
+      });
+      debugModelLog(outcome.fallback ? "warn" : "log", "adapter:model:outcome", {
+        source: outcome.source,
+        fallbackReason: outcome.fallbackReason,
+        model: outcome.model,
+        attempts: outcome.attempts,
+        latencyMs: outcome.latencyMs,
+        promptTemplate: outcome.promptTemplate,
+        sanitized: outcome.sanitized,
       });
+      const { payload, ...summary } = outcome;
+      const payloadJson = JSON.stringify(payload);
       debugModelLog("log", "adapter:model:response", {
         payloadLength: payloadJson.length,
       });
       debugModelLog("log", "adapter:model:response:json", {
         payloadJson,
       });
-      return payloadJson;
+      return { payloadJson, outcome: summary };
     } catch (error) {
       debugModelLog("error", "adapter:model:error", error);
       throw error;
+    } finally {
+      if (EmbeddedModelAdapter.activeRequestId === requestId) {
+        EmbeddedModelAdapter.activeRequestId = null;
+      }
     }
   }
 }
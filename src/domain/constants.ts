@@ -54,13 +54,3 @@ export const DEFAULT_PROFILE = {
   mood: MOODS[0],
   personality: PERSONALITIES[0],
 };
-
-export const DEFAULT_SAMPLING_PARAMS = {
-  temperature: 0.45,
-  topP: 0.9,
-  topK: 50,
-  repeatPenalty: 1.1,
-  maxTokens: 1200,
-  seed: null,
-  stop: [],
-};
@@ -1,15 +1,83 @@
 export type Route = "welcome" | "reading";
 
+export type ModelLoadStage =
+  | "locating"
+  | "verifying"
+  | "building"
+  | "building_full_path";
+
+export interface ModelInfo {
+  version: number;
+  tensorCount: number;
+  name: string | null;
+  architecture: string | null;
+  parameterCount: number;
+  quantization: string | null;
+  contextLength: number | null;
+  blockCount: number | null;
+  embeddingLength: number | null;
+  headCount: number | null;
+  headCountKv: number | null;
+  chatTemplate: string | null;
+  tokenizerModel: string | null;
+  license: string | null;
+}
+
+export type MemoryVerdict = "fits" | "tight" | "insufficient";
+
+export interface MemoryCheck {
+  modelPath: string;
+  verdict: MemoryVerdict;
+  requiredBytes: number;
+  lowMemoryRequiredBytes: number;
+  availableBytes: number;
+  freeSwapBytes: number;
+  lowMemoryMode: boolean;
+  message: string | null;
+}
+
+export interface SystemResources {
+  totalMemoryBytes: number;
+  availableMemoryBytes: number;
+  totalSwapBytes: number;
+  freeSwapBytes: number;
+  cpuCount: number;
+  lowMemoryMode: boolean;
+  memoryCheck: MemoryCheck | null;
+}
+
 export type ModelStatus =
   | { status: "unloaded" }
-  | { status: "loading"; progress: number }
+  | {
+      status: "loading";
+      progress: number;
+      stage: ModelLoadStage;
+      etaMs: number | null;
+      bytesDone: number;
+      bytesTotal: number;
+      // The build reports no progress; `progress` holds until it ends.
+      indeterminate: boolean;
+    }
   | {
       status: "loaded";
       modelPath: string;
       modelSizeMb: number;
       modelSizeBytes: number;
+      modelInfo: ModelInfo | null;
+      backend: "embedded" | "openai";
     }
-  | { status: "error"; message: string };
+  | { status: "unloading" }
+  | { status: "error"; message: string }
+  | { status: "engine_unavailable"; message: string }
+  | {
+      status: "corrupt";
+      modelPath: string;
+      message: string;
+      expectedSha256: string;
+      actualSha256: string | null;
+      expectedBytes: number;
+      actualBytes: number;
+    };
 
 export interface SamplingParams {
   temperature: number;
@@ -19,6 +87,46 @@ export interface SamplingParams {
   maxTokens: number;
   seed: number | null;
   stop: string[];
+  timeoutMs?: number | null;
+  minP?: number | null;
+  frequencyPenalty?: number | null;
+  presencePenalty?: number | null;
+  dry?: DryParams | null;
+  logitBias?: Record<string, number> | null;
+}
+
+export interface DryParams {
+  multiplier: number;
+  base?: number | null;
+  allowedLength?: number | null;
+  sequenceBreakers?: string[] | null;
+}
+
+export interface SamplingPreset {
+  name: string;
+  builtin: boolean;
+  params: SamplingParams;
+}
+
+export interface StubVocabulary {
+  titles: string[];
+  // Templates; `{mood}`, `{personality}` and `{sign}` are filled in.
+  openings: string[];
+  middles: string[];
+  closers: string[];
+  themes: string[];
+  affirmations: string[];
+  luckyColors: string[];
+}
+
+export interface Persona {
+  id: string;
+  name: string;
+  description: string;
+  systemPrompt: string;
+  sampling: SamplingParams;
+  stub: StubVocabulary;
+  builtin: boolean;
 }
 
 export type StreamEvent =
@@ -26,6 +134,90 @@ export type StreamEvent =
   | { kind: "chunk"; chunk: string }
   | { kind: "end" };
 
+export type FallbackReason =
+  | { kind: "timed_out"; message: string }
+  | { kind: "invalid_output"; message: string }
+  | { kind: "context_overflow"; message: string }
+  | { kind: "inference_failed"; message: string };
+
+export interface GenerationOutcome<T> {
+  payload: T;
+  source: "model" | "stub";
+  fallback: boolean;
+  fallbackReason: FallbackReason | null;
+  model: string | null;
+  attempts: number;
+  latencyMs: number;
+  promptTemplate: PromptStamp | null;
+  sanitized: SanitizedField[];
+}
+
+// Everything about a generation except its payload.
+export type OutcomeSummary = Omit<GenerationOutcome<unknown>, "payload">;
+
+export interface SanitizedField {
+  field: "name" | "birthdate" | "mood" | "personality" | "date";
+  actions: ("truncated" | "characters_stripped" | "instruction_neutralized")[];
+}
+
+export interface PromptStamp {
+  name: string;
+  version: number;
+  overridden: boolean;
+  includes: string[];
+}
+
+export interface TokenEstimate {
+  promptTokens: number;
+  exact: boolean;
+  contextLength: number | null;
+  availableTokens: number | null;
+}
+
+export interface InferenceMetrics {
+  requestId: string;
+  kind: "reading" | "reading_stream" | "dashboard";
+  attempt: number;
+  promptTokens: number | null;
+  completionTokens: number | null;
+  timeToFirstTokenMs: number | null;
+  durationMs: number;
+  tokensPerSecond: number | null;
+  promptTokensPerSecond: number | null;
+  prefixCacheHit: boolean | null;
+  finishReason: string | null;
+  maxTokens: number;
+  truncated: boolean;
+}
+
+export interface MetricsSummary {
+  kind: InferenceMetrics["kind"];
+  calls: number;
+  truncatedCalls: number;
+  prefixCacheHits: number;
+  promptTokens: number;
+  completionTokens: number;
+  maxCompletionTokens: number;
+  meanTimeToFirstTokenMs: number | null;
+  meanTokensPerSecond: number | null;
+}
+
+export interface ModelMetrics {
+  summaries: MetricsSummary[];
+  recent: InferenceMetrics[];
+}
+
+export type GenerationPriority = "interactive" | "background";
+
+export type QueueEvent =
+  | {
+      status: "queued";
+      requestId: string;
+      position: number;
+      priority: GenerationPriority;
+    }
+  | { status: "started"; requestId: string; waitedMs: number };
+
 export interface ProfileDraft {
   name: string;
   birthdate: string;
@@ -90,6 +282,8 @@ export interface AppState {
     current: DashboardPayload | null;
     history: DashboardPayload[];
     error: string | null;
+    // How the current reading was produced; null when the frontend's stub made it.
+    outcome: OutcomeSummary | null;
   };
   ui: {
     route: Route;
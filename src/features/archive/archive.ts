@@ -44,7 +44,7 @@ function renderArchiveList() {
       try {
         const payload = JSON.parse(raw) as AppState["reading"]["current"];
         if (!payload) return;
-        store.applyEvents([{ type: "ReadingGenerated", reading: payload }]);
+        store.applyEvents([{ type: "ReadingGenerated", reading: payload, outcome: null }]);
         saveSnapshot(store.getState());
         toggleArchive(false);
       } catch {
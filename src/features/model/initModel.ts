@@ -1,9 +1,20 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import type { ModelStatus } from "../../domain/types";
+import type {
+  InferenceMetrics,
+  MemoryCheck,
+  ModelStatus,
+  QueueEvent,
+} from "../../domain/types";
 import { debugLog, debugModelLog } from "../../debug/logger";
 import { commandBus, store } from "../../app/runtime";
 
+// Only a load can stall; every other status (including `unloaded` after a
+// deliberate `unload_model`) is where the model is meant to stay.
+function isLoading(status: ModelStatus) {
+  return status.status === "loading";
+}
+
 export function initModel() {
   let warnTimeout: number | null = null;
   let errorTimeout: number | null = null;
@@ -27,6 +38,9 @@ export function initModel() {
     }, 5000);
     errorTimeout = window.setTimeout(() => {
       const current = store.getState().model.status;
+      // The build sends one indeterminate update and then nothing until it
+      // ends, however long a large model takes; that silence is not a stall.
+      if (current.status === "loading" && current.indeterminate) return;
       if (current.status === "loading" || current.status === "unloaded") {
         commandBus.execute({
           type: "ModelStatusUpdated",
@@ -45,10 +59,10 @@ export function initModel() {
         loadStart = performance.now();
       }
       debugModelLog("log", "model:init:response", status);
-      if (status.status === "loaded" || status.status === "error") {
-        clearFallbacks();
-      } else {
+      if (isLoading(status)) {
         scheduleFallbacks();
+      } else {
+        clearFallbacks();
       }
     })
     .catch((error) => {
@@ -72,10 +86,26 @@ export function initModel() {
       ...event.payload,
       ...(loadDurationMs !== undefined ? { loadDurationMs } : {}),
     });
-    if (event.payload.status === "loaded" || event.payload.status === "error") {
-      clearFallbacks();
-    } else {
+    if (isLoading(event.payload)) {
       scheduleFallbacks();
+    } else {
+      clearFallbacks();
     }
   });
+
+  listen<MemoryCheck>("model:memory", (event) => {
+    debugModelLog(
+      event.payload.verdict === "insufficient" ? "error" : "warn",
+      "model:memory",
+      event.payload
+    );
+  });
+
+  listen<QueueEvent>("model:queue", (event) => {
+    debugModelLog("log", `model:queue:${event.payload.status}`, event.payload);
+  });
+
+  listen<InferenceMetrics>("model:metrics", (event) => {
+    debugModelLog(event.payload.truncated ? "warn" : "log", "model:metrics", event.payload);
+  });
 }
@@ -1,4 +1,4 @@
-import type { AppState, DashboardPayload, ProfileDraft, SamplingParams } from "../domain/types";
+import type { AppState, DashboardPayload, OutcomeSummary, ProfileDraft } from "../domain/types";
 import { HoroscopeRepository } from "../repository/horoscopeRepository";
 import { debugModelLog } from "../debug/logger";
 import { zodiacSign } from "../domain/zodiac";
@@ -6,17 +6,6 @@ import { buildDashboardPrompt } from "./dashboardPrompt";
 import { parseDashboardPayload } from "../domain/dashboard";
 import { StubAdapter } from "../adapters/stubAdapter";
 
-// Keep in sync with domain/constants DEFAULT_SAMPLING_PARAMS for runtime fallback.
-const DEFAULT_SAMPLING_PARAMS: SamplingParams = {
-  temperature: 0.45,
-  topP: 0.9,
-  topK: 50,
-  repeatPenalty: 1.1,
-  maxTokens: 3600,
-  seed: null,
-  stop: [],
-};
-
 export interface PipelineContext {
   profile: ProfileDraft;
   dateISO: string;
@@ -25,7 +14,14 @@ export interface PipelineContext {
   templateJson?: string;
   payloadJson?: string;
   payload?: DashboardPayload;
-  sampling?: SamplingParams;
+  outcome?: OutcomeSummary | null;
+  // Sampling itself comes from the persona in Rust; the pipeline only varies the seed.
+  seed?: number;
+}
+
+export interface PipelineResult {
+  payload: DashboardPayload;
+  outcome: OutcomeSummary | null;
 }
 
 export interface PipelineStep {
@@ -68,13 +64,17 @@ export class InvokeModelStep implements PipelineStep {
     debugModelLog("log", "pipeline:invoke:start", {
       modelStatus: state.model.status,
     });
-    context.payloadJson = await this.repository.generate(
+    const generated = await this.repository.generate(
       context.profile,
       context.dateISO,
       context.prompt,
       state.model.status,
-      context.sampling
+      undefined,
+      undefined,
+      context.seed
     );
+    context.payloadJson = generated.payloadJson;
+    context.outcome = generated.outcome;
     debugModelLog("log", "pipeline:invoke:done", {
       payloadLength: context.payloadJson?.length,
     });
@@ -131,6 +131,8 @@ export class ValidatePayloadStep implements PipelineStep {
       throw new Error(stubResult.error);
     }
     context.payload = stubResult.payload;
+    // The backend's outcome describes the payload that was just discarded.
+    context.outcome = null;
   }
 }
 
@@ -188,29 +190,21 @@ function describeJsonErrorLocation(
   return { position: safePosition, line, column, snippet };
 }
 
-function buildSamplingParams(
-  profile: ProfileDraft,
-  dateISO: string,
-  state: AppState
-): SamplingParams {
+function buildSeed(profile: ProfileDraft, dateISO: string, state: AppState): number {
   const base = `${dateISO}|${profile.name}|${profile.birthdate}`;
   const baseSeed = hashSeed(base);
   const historySalt = state.reading.history.filter(
     (reading) => reading.meta.dateISO === dateISO
   ).length;
   const currentSalt = state.reading.current?.meta.dateISO === dateISO ? 1 : 0;
-  const seed = (baseSeed + historySalt + currentSalt) >>> 0;
-  return {
-    ...DEFAULT_SAMPLING_PARAMS,
-    seed,
-  };
+  return (baseSeed + historySalt + currentSalt) >>> 0;
 }
 
 export async function runReadingPipeline(
   profile: ProfileDraft,
   dateISO: string,
   state: AppState
-): Promise<DashboardPayload> {
+): Promise<PipelineResult> {
   const repository = new HoroscopeRepository();
   const steps: PipelineStep[] = [
     new BuildPromptStep(),
@@ -231,7 +225,7 @@ export async function runReadingPipeline(
     profile,
     dateISO,
     localeDateLabel,
-    sampling: buildSamplingParams(profile, dateISO, state),
+    seed: buildSeed(profile, dateISO, state),
   };
   for (const step of steps) {
     debugModelLog("log", "pipeline:step:start", {
@@ -245,5 +239,5 @@ export async function runReadingPipeline(
   if (!context.payload) {
     throw new Error("Unable to generate dashboard payload.");
   }
-  return context.payload;
+  return { payload: context.payload, outcome: context.outcome ?? null };
 }
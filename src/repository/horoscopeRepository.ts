@@ -1,9 +1,8 @@
 import { emit } from "@tauri-apps/api/event";
 import type { ModelStatus, ProfileDraft, SamplingParams, StreamEvent } from "../domain/types";
-import type { HoroscopeAdapter } from "../adapters/modelAdapter";
-import { EmbeddedModelAdapter } from "../adapters/modelAdapter";
+import type { GeneratedPayload, HoroscopeAdapter } from "../adapters/modelAdapter";
+import { EmbeddedModelAdapter, isCancelledGeneration } from "../adapters/modelAdapter";
 import { StubAdapter } from "../adapters/stubAdapter";
-import { DEFAULT_SAMPLING_PARAMS } from "../domain/constants";
 import { debugModelLog } from "../debug/logger";
 
 const STREAM_CHUNK_SIZE = 28;
@@ -25,8 +24,10 @@ export class HoroscopeRepository {
     date: string,
     prompt: string | undefined,
     status: ModelStatus,
-    sampling: SamplingParams = DEFAULT_SAMPLING_PARAMS
-  ): Promise<string> {
+    sampling?: SamplingParams,
+    persona?: string,
+    seed?: number
+  ): Promise<GeneratedPayload> {
     const startedAt = performance.now();
     debugModelLog("log", "repository:generate:start", {
       status,
@@ -36,19 +37,24 @@ export class HoroscopeRepository {
     if (status.status === "loaded") {
       try {
         debugModelLog("log", "repository:generate:using:model");
-        const payload = await this.embeddedAdapter.generate(
+        const generated = await this.embeddedAdapter.generate(
           profile,
           date,
           prompt,
-          sampling
+          sampling,
+          persona,
+          seed
         );
         debugModelLog("log", "repository:generate:complete", {
-          source: "model",
+          source: generated.outcome?.source ?? "model",
           durationMs: Math.round(performance.now() - startedAt),
-          payloadLength: payload.length,
+          payloadLength: generated.payloadJson.length,
         });
-        return payload;
-      } catch {
+        return generated;
+      } catch (error) {
+        // A cancelled generation was superseded; a stub reading would only
+        // flash up before the newer one replaces it.
+        if (isCancelledGeneration(error)) throw error;
         debugModelLog("warn", "repository:generate:model:error", {
           message: "Model adapter failed. Falling back to stub.",
         });
@@ -58,7 +64,7 @@ export class HoroscopeRepository {
           durationMs: Math.round(performance.now() - startedAt),
           payloadLength: payload.length,
         });
-        return payload;
+        return { payloadJson: payload, outcome: null };
       }
     }
     debugModelLog("warn", "repository:generate:using:stub", {
@@ -70,7 +76,7 @@ export class HoroscopeRepository {
       durationMs: Math.round(performance.now() - startedAt),
       payloadLength: payload.length,
     });
-    return payload;
+    return { payloadJson: payload, outcome: null };
   }
 
   private async emitStubStream(profile: ProfileDraft, date: string) {
@@ -1,6 +1,7 @@
 import type { AppState, ProfileDraft } from "../domain/types";
 import { profileSpec } from "../domain/specs";
 import { runReadingPipeline } from "../pipeline/readingPipeline";
+import { EmbeddedModelAdapter, isCancelledGeneration } from "../adapters/modelAdapter";
 import type { DomainEvent } from "./events";
 import { AsyncQueue } from "./queue";
 import { debugLog, debugModelLog, isDebugEnabled } from "../debug/logger";
@@ -100,6 +101,9 @@ export class CommandBus {
       ]);
     }
 
+    // Generations run one at a time, so a reading for an older profile would
+    // otherwise finish before this one even starts.
+    EmbeddedModelAdapter.cancelActive();
     const snapshotBefore = this.context.getState();
     this.queue.enqueue(async () => {
       debugModelLog("log", "command:GenerateReading:queue:begin");
@@ -112,7 +116,7 @@ export class CommandBus {
         route: this.context.getState().ui.route,
       });
       try {
-        const reading = await runReadingPipeline(
+        const { payload: reading, outcome } = await runReadingPipeline(
           state.profile.saved as ProfileDraft,
           new Date().toISOString().slice(0, 10),
           this.context.getState()
@@ -132,22 +136,28 @@ export class CommandBus {
         });
         this.pushUndo(snapshotBefore);
         this.context.applyEvents([
-          { type: "ReadingGenerated", reading },
+          { type: "ReadingGenerated", reading, outcome },
         ]);
       } catch (error) {
-        const message = error instanceof Error ? error.message : "The stars were quiet.";
-        const durationMs = Math.round(performance.now() - startedAt);
-        debugLog("error", "command:GenerateReading:timing", {
-          durationMs,
-          message,
-        });
-        debugModelLog("error", "command:GenerateReading:timing", {
-          durationMs,
-          message,
-        });
-        debugLog("error", "command:GenerateReading:pipeline:error", error);
-        debugModelLog("error", "command:GenerateReading:failed", { message, error });
-        this.context.applyEvents([{ type: "ReadingGenerationFailed", error: message }]);
+        if (isCancelledGeneration(error)) {
+          // A newer GenerateReading cancelled this one and is queued behind it.
+          debugModelLog("log", "command:GenerateReading:cancelled");
+          this.context.applyEvents([{ type: "ReadingGenerationCancelled" }]);
+        } else {
+          const message = error instanceof Error ? error.message : "The stars were quiet.";
+          const durationMs = Math.round(performance.now() - startedAt);
+          debugLog("error", "command:GenerateReading:timing", {
+            durationMs,
+            message,
+          });
+          debugModelLog("error", "command:GenerateReading:timing", {
+            durationMs,
+            message,
+          });
+          debugLog("error", "command:GenerateReading:pipeline:error", error);
+          debugModelLog("error", "command:GenerateReading:failed", { message, error });
+          this.context.applyEvents([{ type: "ReadingGenerationFailed", error: message }]);
+        }
       }
       debugModelLog("log", "command:GenerateReading:queue:end");
       debugLog("log", "command:GenerateReading:queue:end");
@@ -178,6 +188,7 @@ function defaultResetState(state: AppState): AppState {
       current: null,
       history: [],
       error: null,
+      outcome: null,
     },
     ui: {
       ...state.ui,
@@ -1,4 +1,11 @@
-import type { AppState, DashboardPayload, ModelStatus, ProfileDraft, Route } from "../domain/types";
+import type {
+  AppState,
+  DashboardPayload,
+  ModelStatus,
+  OutcomeSummary,
+  ProfileDraft,
+  Route,
+} from "../domain/types";
 
 export type DomainEvent =
   | { type: "ProfileValidated"; profile: ProfileDraft }
@@ -6,7 +13,8 @@ export type DomainEvent =
   | { type: "ProfileSaved"; profile: ProfileDraft }
   | { type: "RouteChanged"; route: Route }
   | { type: "ReadingGenerationStarted" }
-  | { type: "ReadingGenerated"; reading: DashboardPayload }
+  | { type: "ReadingGenerated"; reading: DashboardPayload; outcome: OutcomeSummary | null }
   | { type: "ReadingGenerationFailed"; error: string }
+  | { type: "ReadingGenerationCancelled" }
   | { type: "ModelStatusChanged"; status: ModelStatus }
   | { type: "StateRehydrated"; state: AppState };
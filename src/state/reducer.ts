@@ -1,4 +1,4 @@
-import type { AppState } from "../domain/types";
+import type { AppState, FallbackReason } from "../domain/types";
 import type { DomainEvent } from "./events";
 
 export function reducer(state: AppState, event: DomainEvent): AppState {
@@ -54,7 +54,8 @@ export function reducer(state: AppState, event: DomainEvent): AppState {
           },
         },
       };
-    case "ReadingGenerated":
+    case "ReadingGenerated": {
+      const fallbackReason = event.outcome?.fallback ? event.outcome.fallbackReason : null;
       return {
         ...state,
         reading: {
@@ -63,6 +64,7 @@ export function reducer(state: AppState, event: DomainEvent): AppState {
             ? [state.reading.current, ...state.reading.history].slice(0, 10)
             : state.reading.history,
           error: null,
+          outcome: event.outcome,
         },
         ui: {
           ...state.ui,
@@ -70,8 +72,12 @@ export function reducer(state: AppState, event: DomainEvent): AppState {
             ...state.ui.busyFlags,
             generating: false,
           },
+          toasts: fallbackReason
+            ? [...state.ui.toasts, describeFallback(fallbackReason)].slice(-3)
+            : state.ui.toasts,
         },
       };
+    }
     case "ReadingGenerationFailed":
       return {
         ...state,
@@ -88,6 +94,17 @@ export function reducer(state: AppState, event: DomainEvent): AppState {
           toasts: [...state.ui.toasts, event.error].slice(-3),
         },
       };
+    case "ReadingGenerationCancelled":
+      return {
+        ...state,
+        ui: {
+          ...state.ui,
+          busyFlags: {
+            ...state.ui.busyFlags,
+            generating: false,
+          },
+        },
+      };
     case "ModelStatusChanged":
       return {
         ...state,
@@ -106,3 +123,17 @@ export function reducer(state: AppState, event: DomainEvent): AppState {
       return state;
   }
 }
+
+// The model was loaded but the offline stub answered instead; say why.
+function describeFallback(reason: FallbackReason): string {
+  switch (reason.kind) {
+    case "timed_out":
+      return "The model took too long, so today's reading comes from the offline deck.";
+    case "invalid_output":
+      return "The model's answer came out garbled, so this reading comes from the offline deck.";
+    case "context_overflow":
+      return "Your reading didn't fit in the model's memory, so it comes from the offline deck.";
+    case "inference_failed":
+      return "The model stumbled, so today's reading comes from the offline deck.";
+  }
+}
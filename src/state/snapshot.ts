@@ -24,6 +24,7 @@ export function getDefaultState(): AppState {
       current: null,
       history: [],
       error: null,
+      outcome: null,
     },
     ui: {
       route: "welcome",
@@ -93,6 +94,7 @@ function reviveDates(state: AppState): AppState {
       current: state.reading.current ? { ...state.reading.current } : null,
       history: state.reading.history.map((reading) => ({ ...reading })),
       error: state.reading.error ?? null,
+      outcome: state.reading.outcome ?? null,
     },
   };
 }
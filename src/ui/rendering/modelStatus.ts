@@ -1,4 +1,4 @@
-import type { AppState } from "../../domain/types";
+import type { AppState, ModelLoadStage, ModelStatus } from "../../domain/types";
 import { debugLog, isDebugEnabled } from "../../debug/logger";
 
 const MIN_LOADING_MS = 1200;
@@ -38,6 +38,20 @@ function scheduleLoadingHide() {
   }, remaining);
 }
 
+const LOAD_STAGE_LABELS: Record<ModelLoadStage, string> = {
+  locating: "Finding the star map…",
+  verifying: "Checking the star map is intact…",
+  building: "Aligning the constellations…",
+  building_full_path: "Aligning the constellations (second attempt)…",
+};
+
+function describeLoadingStage(status: Extract<ModelStatus, { status: "loading" }>) {
+  const label = LOAD_STAGE_LABELS[status.stage] ?? "Preparing the star map…";
+  if (status.etaMs === null || status.etaMs === undefined) return label;
+  const seconds = Math.max(1, Math.round(status.etaMs / 1000));
+  return `${label} about ${seconds}s left`;
+}
+
 export function renderModelStatus(status: AppState["model"]["status"]) {
   const label = document.querySelector<HTMLElement>(
     "#model-status .model-status__label"
@@ -49,11 +63,15 @@ export function renderModelStatus(status: AppState["model"]["status"]) {
     "#app-loading-progress"
   );
   if (!label || !progress) return;
+  // The model build reports no progress, so its bars pulse instead of moving.
+  const indeterminate = status.status === "loading" && status.indeterminate;
+  progress.classList.toggle("is-indeterminate", indeterminate);
+  loadingProgress?.classList.toggle("is-indeterminate", indeterminate);
 
   if (status.status === "loading") {
     label.textContent = "Consulting the constellations…";
     progress.style.width = `${Math.round(status.progress * 100)}%`;
-    if (loadingLabel) loadingLabel.textContent = "Preparing the star map…";
+    if (loadingLabel) loadingLabel.textContent = describeLoadingStage(status);
     if (loadingProgress) {
       loadingProgress.style.width = `${Math.round(status.progress * 100)}%`;
     }
@@ -73,7 +91,10 @@ export function renderModelStatus(status: AppState["model"]["status"]) {
     const sizeLabel = Number.isFinite(status.modelSizeMb)
       ? ` (${status.modelSizeMb.toFixed(1)} MB)`
       : "";
-    label.textContent = `Model loaded: ${status.modelPath}${sizeLabel}.`;
+    label.textContent =
+      status.backend === "openai"
+        ? `Connected to ${status.modelPath}.`
+        : `Model loaded: ${status.modelPath}${sizeLabel}.`;
     progress.style.width = "100%";
     if (loadingLabel) loadingLabel.textContent = "The stars are ready.";
     if (loadingProgress) loadingProgress.style.width = "100%";
@@ -92,12 +113,32 @@ export function renderModelStatus(status: AppState["model"]["status"]) {
         sizeMb: Number.isFinite(status.modelSizeMb)
           ? Number(status.modelSizeMb.toFixed(3))
           : status.modelSizeMb,
+        architecture: status.modelInfo?.architecture ?? null,
+        parameterCount: status.modelInfo?.parameterCount ?? null,
+        quantization: status.modelInfo?.quantization ?? null,
+        contextLength: status.modelInfo?.contextLength ?? null,
       });
     }
-  } else if (status.status === "error") {
+  } else if (status.status === "engine_unavailable") {
+    label.textContent = "Offline readings (this build has no model engine).";
+    progress.style.width = "100%";
+    if (loadingLabel) loadingLabel.textContent = "The stars are ready.";
+    if (loadingProgress) loadingProgress.style.width = "100%";
+    if (loadingShell) {
+      if (!loadedShownAt) {
+        loadedShownAt = Date.now();
+      }
+      scheduleLoadingHide();
+    }
+  } else if (status.status === "error" || status.status === "corrupt") {
     label.textContent = "We will use a gentle offline reading.";
     progress.style.width = "100%";
-    if (loadingLabel) loadingLabel.textContent = "App failed to load.";
+    if (loadingLabel) {
+      loadingLabel.textContent =
+        status.status === "corrupt"
+          ? "The model file is damaged. Copy it again to restore readings."
+          : "App failed to load.";
+    }
     if (loadingProgress) loadingProgress.style.width = "100%";
     if (loadingShell) {
       loadingShell.classList.remove("is-hidden");